@@ -44,4 +44,61 @@ pub use modules::compile::*;
 /// that handles parsing and
 /// processing different formats
 /// of data.
-pub use modules::processors::*;
\ No newline at end of file
+pub use modules::processors::*;
+
+/// Re-exporting the module
+/// containing Mandy's
+/// development server.
+pub use modules::serve::*;
+
+/// Re-exporting the module
+/// to watch a Mandy project
+/// for changes.
+pub use modules::watch::*;
+
+/// Re-exporting the module
+/// containing Mandy's cache
+/// for incremental builds.
+pub use modules::cache::*;
+
+/// Re-exporting the module
+/// that turns errors into
+/// diagnostics pointing to
+/// the failing code.
+pub use modules::diagnostics::*;
+
+/// Re-exporting the module
+/// that reports what Mandy
+/// does while it works.
+pub use modules::logger::*;
+
+/// Re-exporting the module
+/// that finds problems that
+/// do not stop a build.
+pub use modules::warnings::*;
+
+/// Re-exporting the module
+/// that creates new Mandy
+/// projects.
+pub use modules::scaffold::*;
+
+/// Re-exporting the module
+/// that creates content
+/// files from archetypes.
+pub use modules::archetypes::*;
+
+/// Re-exporting the module
+/// that selects the environment
+/// a Mandy project is built for.
+pub use modules::environment::*;
+
+/// Re-exporting the module
+/// that fills in and checks
+/// the options of a Mandy
+/// project.
+pub use modules::config::*;
+
+/// Re-exporting the module
+/// that renders pages from
+/// data files.
+pub use modules::generators::*;
\ No newline at end of file
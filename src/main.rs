@@ -7,11 +7,27 @@ Licensed under the FSL v1.
 /// CLI.
 use mandy::cli;
 
+/// Importing the function
+/// to report an error.
+use mandy::log_error;
+
+/// Importing the function
+/// to exit with a status
+/// code.
+use std::process::exit;
+
 /// The main point of 
 /// entry for the Rust compiler.
+/// Feedback is printed to stdout,
+/// errors are reported through the
+/// logger and the process exits with
+/// the code for the kind of error.
 fn main(){
     match cli(){
         Ok(feedback) => println!("{}", feedback),
-        Err(e) => eprintln!("{}", e)
+        Err(e) => {
+            log_error(&e);
+            exit(e.exit_code());
+        }
     };
 }
\ No newline at end of file
@@ -0,0 +1,445 @@
+/*
+Mandy by Alyx Shang.
+Licensed under the FSL v1.
+*/
+
+/// Importing Rust's standard
+/// API for working with the
+/// file system.
+use std::fs;
+
+/// Importing the "PathBuf"
+/// structure from Rust's standard
+/// library.
+use std::path::PathBuf;
+
+/// Importing the "Component"
+/// enum to check that new content
+/// files stay inside the project.
+use std::path::Component;
+
+/// Importing the "object"
+/// macro from the "liquid"
+/// crate to supply context
+/// to archetypes.
+use liquid::object;
+
+/// Importing the "Template"
+/// structure from the "liquid"
+/// crate to render archetypes.
+use liquid::Template;
+
+/// Importing the "ParserBuilder"
+/// structure from the "liquid"
+/// crate to parse archetypes.
+use liquid::ParserBuilder;
+
+/// Importing the "Mapping"
+/// structure from the "serde_yml"
+/// crate to read front matter in
+/// the order it was written in.
+use serde_yml::Mapping;
+
+/// Importing the "SystemTime"
+/// structure to find out the
+/// current date.
+use std::time::SystemTime;
+
+/// Importing the "UNIX_EPOCH"
+/// constant to find out the
+/// current date.
+use std::time::UNIX_EPOCH;
+
+/// Importing Mandy's error-handling
+/// structure to handle errors.
+use super::err::MandyErr;
+
+/// Importing the structure that
+/// holds what is known about
+/// an error.
+use super::err::ErrInfo;
+
+/// Importing the function to split
+/// the front matter from the content
+/// of a content file.
+use super::processors::split_front_matter;
+
+/// Importing the function to turn
+/// errors from the "liquid" crate
+/// into diagnostics.
+use super::diagnostics::liquid_error;
+
+/// Importing the function to report
+/// a written file.
+use super::logger::log_written;
+
+/// The directory archetypes are
+/// stored in inside a Mandy project.
+pub const ARCHETYPES_DIR: &str = "archetypes";
+
+/// The name of the archetype used for
+/// content files in directories without
+/// an archetype of their own.
+pub const DEFAULT_ARCHETYPE: &str = "default";
+
+/// Turns the supplied text into a slug that only contains lowercase
+/// letters, digits, and single dashes.
+pub fn slugify(subject: &str) -> String {
+    let mut result: String = String::new();
+    for c in subject.chars() {
+        if c.is_alphanumeric() {
+            result.extend(c.to_lowercase());
+        }
+        else if !result.is_empty() && !result.ends_with('-') {
+            result.push('-');
+        }
+    }
+    result.trim_end_matches('-').to_string()
+}
+
+/// Turns the supplied slug into a title by replacing dashes and
+/// underscores with spaces and capitalizing every word.
+pub fn title_from_slug(slug: &str) -> String {
+    let mut words: Vec<String> = Vec::new();
+    for word in slug.split(['-', '_', ' ']) {
+        let mut chars = word.chars();
+        if let Some(first) = chars.next() {
+            words.push(first.to_uppercase().chain(chars).collect());
+        }
+    }
+    words.join(" ")
+}
+
+/// Escapes the supplied text for use inside a double-quoted
+/// YAML string, such as "title: \"{{ yaml_title }}\"".
+pub fn escape_yaml_string(text: &str) -> String {
+    let mut result: String = String::new();
+    for c in text.chars() {
+        match c {
+            '\\' => result.push_str("\\\\"),
+            '"' => result.push_str("\\\""),
+            '\n' => result.push_str("\\n"),
+            '\t' => result.push_str("\\t"),
+            c if c.is_control() => result.push_str(&format!("\\u{:04x}", c as u32)),
+            c => result.push(c)
+        };
+    }
+    result
+}
+
+/// Returns the current date in the
+/// format "YYYY-MM-DD" in UTC.
+pub fn current_date() -> String {
+    let seconds: u64 = match SystemTime::now().duration_since(UNIX_EPOCH) {
+        Ok(elapsed) => elapsed.as_secs(),
+        Err(_e) => 0
+    };
+    let days: i64 = (seconds / 86400) as i64 + 719468;
+    let era: i64 = days.div_euclid(146097);
+    let day_of_era: i64 = days.rem_euclid(146097);
+    let year_of_era: i64 = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year: i64 = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month: i64 = (5 * day_of_year + 2) / 153;
+    let day: i64 = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month: i64 = if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 };
+    let year: i64 = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// Reads the front matter of every content file directly inside the
+/// supplied directory. Files without readable front matter are skipped.
+/// The front matter of every file is returned in the order it was written in.
+pub fn read_sibling_front_matter(section_buf: &PathBuf) -> Vec<Mapping> {
+    let mut result: Vec<Mapping> = Vec::new();
+    let entries: fs::ReadDir = match fs::read_dir(section_buf) {
+        Ok(entries) => entries,
+        Err(_e) => return result
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && path.extension().map(|ext| ext == "markdown").unwrap_or(false))
+        .collect();
+    paths.sort();
+    for path in paths {
+        let src: String = match fs::read_to_string(&path) {
+            Ok(src) => src,
+            Err(_e) => continue
+        };
+        let front_matter: String = match split_front_matter(&src) {
+            Some((front_matter, _content, _lines_before, _content_lines_before)) => front_matter,
+            None => continue
+        };
+        match serde_yml::from_str::<Mapping>(&front_matter) {
+            Ok(mapping) => result.push(mapping),
+            Err(_e) => {}
+        };
+    }
+    result
+}
+
+/// Returns the layout to use for new content files in a directory. This is
+/// the layout most content files in the directory already use. If the directory
+/// has no content files, the first layout of the project is used.
+pub fn find_section_layout(dir: &String, siblings: &Vec<Mapping>) -> String {
+    let mut counts: Vec<(String, usize)> = Vec::new();
+    for sibling in siblings {
+        if let Some(layout) = sibling.get("layout").and_then(|layout| layout.as_str()) {
+            match counts.iter_mut().find(|(name, _count)| name == layout) {
+                Some((_name, count)) => *count += 1,
+                None => counts.push((layout.to_string(), 1))
+            };
+        }
+    }
+    let mut most_used: Option<(String, usize)> = None;
+    for (name, count) in counts {
+        let is_more: bool = match &most_used {
+            Some((_name, most)) => count > *most,
+            None => true
+        };
+        if is_more {
+            most_used = Some((name, count));
+        }
+    }
+    match most_used {
+        Some((name, _count)) => name,
+        None => {
+            let mut layouts_buf: PathBuf = PathBuf::new();
+            layouts_buf.push(dir);
+            layouts_buf.push("layouts");
+            let mut names: Vec<String> = match fs::read_dir(&layouts_buf) {
+                Ok(entries) => entries
+                    .filter_map(|entry| entry.ok())
+                    .map(|entry| entry.path())
+                    .filter(|path| path.extension().map(|ext| ext == "liquid").unwrap_or(false))
+                    .filter_map(|path| path.file_stem().map(|stem| stem.to_string_lossy().to_string()))
+                    .collect(),
+                Err(_e) => Vec::new()
+            };
+            names.sort();
+            match names.first() {
+                Some(name) => name.clone(),
+                None => String::from("default")
+            }
+        }
+    }
+}
+
+/// Builds an archetype from the content files that already exist in a
+/// directory. The archetype sets the layout and the title, and sets the
+/// date if all existing files have one.
+pub fn build_default_archetype(siblings: &[Mapping]) -> String {
+    let mut lines: Vec<String> = vec![
+        String::from("---"),
+        String::from("layout: {{ layout }}"),
+        String::from("title: \"{{ yaml_title }}\"")
+    ];
+    if !siblings.is_empty() && siblings.iter().all(|sibling| sibling.contains_key("date")) {
+        lines.push(String::from("date: \"{{ date }}\""));
+    }
+    lines.push(String::from("---"));
+    lines.push(String::new());
+    lines.join("\n")
+}
+
+/// Creates a new content file at the supplied path inside the Mandy project in the
+/// supplied directory, for example "posts/my-title". The file is created from the
+/// archetype for its directory, "archetypes/posts.markdown" in this example. If this
+/// archetype does not exist, "archetypes/default.markdown" is used, and if that does
+/// not exist either, an archetype is built from the content files already in the
+/// directory. Archetypes are Liquid templates that can use the "title", "yaml_title",
+/// "slug", "date", "layout", and "section" variables. The "yaml_title" variable holds
+/// the title escaped for use inside a double-quoted YAML string. The path must stay
+/// inside the project. Returns the path of the new file in a "Result" type.
+pub fn new_content_file(dir: &String, target: &String) -> Result<PathBuf, MandyErr>{
+    let target_buf: PathBuf = PathBuf::from(target.trim_end_matches(".markdown"));
+    let is_inside: bool = target_buf
+        .components()
+        .all(|component| matches!(component, Component::Normal(_) | Component::CurDir));
+    if !is_inside {
+        let e: String = format!("The path \"{}\" must be relative to the Mandy project and must not contain \"..\".", target);
+        return Err::<PathBuf, MandyErr>(MandyErr::Usage(Box::new(ErrInfo::new(&e.to_string()))));
+    }
+    let name: String = match target_buf.file_name() {
+        Some(name) => name.to_string_lossy().to_string(),
+        None => {
+            let e: String = format!("The path \"{}\" does not name a content file.", target);
+            return Err::<PathBuf, MandyErr>(MandyErr::Usage(Box::new(ErrInfo::new(&e.to_string()))));
+        }
+    };
+    let slug: String = slugify(&name);
+    if slug.is_empty() {
+        let e: String = format!("The name \"{}\" does not contain any letters or digits.", &name);
+        return Err::<PathBuf, MandyErr>(MandyErr::Usage(Box::new(ErrInfo::new(&e.to_string()))));
+    }
+    let section_buf: PathBuf = match target_buf.parent() {
+        Some(parent) => parent.to_path_buf(),
+        None => PathBuf::new()
+    };
+    let section: String = section_buf.display().to_string();
+    let mut project_section_buf: PathBuf = PathBuf::new();
+    project_section_buf.push(dir);
+    project_section_buf.push(&section_buf);
+    let mut file_buf: PathBuf = project_section_buf.clone();
+    file_buf.push(format!("{}.markdown", &slug));
+    if file_buf.exists() {
+        let e: String = format!("The content file \"{}\" already exists.", file_buf.display());
+        return Err::<PathBuf, MandyErr>(MandyErr::Usage(Box::new(ErrInfo::new(&e.to_string()))));
+    }
+    let mut archetypes_buf: PathBuf = PathBuf::new();
+    archetypes_buf.push(dir);
+    archetypes_buf.push(ARCHETYPES_DIR);
+    let mut candidates: Vec<PathBuf> = Vec::new();
+    if !section.is_empty() {
+        candidates.push(archetypes_buf.join(format!("{}.markdown", &section)));
+    }
+    candidates.push(archetypes_buf.join(format!("{}.markdown", DEFAULT_ARCHETYPE)));
+    let siblings: Vec<Mapping> = read_sibling_front_matter(&project_section_buf);
+    let (archetype_buf, archetype): (PathBuf, String) = match candidates.into_iter().find(|candidate| candidate.is_file()) {
+        Some(candidate) => {
+            match fs::read_to_string(&candidate) {
+                Ok(archetype) => (candidate, archetype),
+                Err(e) => return Err::<PathBuf, MandyErr>(MandyErr::Io(Box::new(ErrInfo::from_source(e))).with_path(&candidate))
+            }
+        },
+        None => (PathBuf::new(), build_default_archetype(&siblings))
+    };
+    let sources: Vec<(PathBuf, String)> = vec![(archetype_buf, archetype.clone())];
+    let parser = match ParserBuilder::with_stdlib().build() {
+        Ok(parser) => parser,
+        Err(e) => return Err::<PathBuf, MandyErr>(liquid_error(e, &sources, None))
+    };
+    let template: Template = match parser.parse(&archetype) {
+        Ok(template) => template,
+        Err(e) => return Err::<PathBuf, MandyErr>(liquid_error(e, &sources, None))
+    };
+    let title: String = title_from_slug(&name);
+    let globals = object!({
+        "yaml_title": escape_yaml_string(&title),
+        "title": title,
+        "slug": slug,
+        "date": current_date(),
+        "layout": find_section_layout(dir, &siblings),
+        "section": section
+    });
+    let contents: String = match template.render(&globals) {
+        Ok(contents) => contents,
+        Err(e) => return Err::<PathBuf, MandyErr>(liquid_error(e, &sources, None))
+    };
+    let _create_op: () = match fs::create_dir_all(&project_section_buf) {
+        Ok(_create_op) => _create_op,
+        Err(e) => return Err::<PathBuf, MandyErr>(MandyErr::Io(Box::new(ErrInfo::from_source(e))).with_path(&project_section_buf))
+    };
+    let _write_op: () = match fs::write(&file_buf, &contents) {
+        Ok(_write_op) => _write_op,
+        Err(e) => return Err::<PathBuf, MandyErr>(MandyErr::Io(Box::new(ErrInfo::from_source(e))).with_path(&file_buf))
+    };
+    log_written(&file_buf, &None);
+    Ok(file_buf)
+}
+
+/// Tests for turning names
+/// into slugs and titles and
+/// for creating content files.
+#[cfg(test)]
+mod tests {
+
+    /// Importing Rust's standard
+    /// API for working with the
+    /// file system.
+    use std::fs;
+
+    /// Importing the "PathBuf"
+    /// structure from Rust's standard
+    /// library.
+    use std::path::PathBuf;
+
+    /// Importing the structure
+    /// for directories created
+    /// for tests.
+    use crate::modules::testing::TempDir;
+
+    /// Importing the function
+    /// to create content files.
+    use super::new_content_file;
+
+    /// Importing the function
+    /// to find out the current
+    /// date.
+    use super::current_date;
+
+    /// Importing the function
+    /// to turn names into slugs.
+    use super::slugify;
+
+    /// Importing the function
+    /// to turn slugs into titles.
+    use super::title_from_slug;
+
+    /// Importing the function
+    /// to escape text for YAML
+    /// strings.
+    use super::escape_yaml_string;
+
+    #[test]
+    fn slugifies_names(){
+        assert_eq!(slugify("Hello, World!"), "hello-world");
+        assert_eq!(slugify("--a--b--"), "a-b");
+        assert_eq!(slugify("Ünïcode Café"), "ünïcode-café");
+        assert_eq!(slugify("!!!"), "");
+    }
+
+    #[test]
+    fn titles_slugs(){
+        assert_eq!(title_from_slug("hello-world_again"), "Hello World Again");
+        assert_eq!(title_from_slug("--"), "");
+    }
+
+    #[test]
+    fn escapes_yaml_strings(){
+        assert_eq!(escape_yaml_string("plain"), "plain");
+        assert_eq!(escape_yaml_string("say \"hi\""), "say \\\"hi\\\"");
+        assert_eq!(escape_yaml_string("a\\b\nc\td"), "a\\\\b\\nc\\td");
+        assert_eq!(escape_yaml_string("\u{7}"), "\\u0007");
+        let yaml: String = format!("title: \"{}\"", escape_yaml_string("A \"b\": c\n# d"));
+        let parsed: serde_yml::Value = serde_yml::from_str(&yaml).unwrap();
+        assert_eq!(parsed["title"].as_str(), Some("A \"b\": c\n# d"));
+    }
+
+    #[test]
+    fn prefers_the_archetype_of_the_section(){
+        let project: TempDir = TempDir::new("archetype-order");
+        project.write("archetypes/posts.markdown", "{{ section }}|{{ title }}|{{ yaml_title }}|{{ slug }}");
+        project.write("archetypes/default.markdown", "default|{{ title }}");
+        let post: PathBuf = new_content_file(&project.dir(), &String::from("posts/say-\"hi\"")).unwrap();
+        assert_eq!(post, project.path.join("posts/say-hi.markdown"));
+        assert_eq!(fs::read_to_string(&post).unwrap(), "posts|Say \"hi\"|Say \\\"hi\\\"|say-hi");
+        let page: PathBuf = new_content_file(&project.dir(), &String::from("pages/about-us")).unwrap();
+        assert_eq!(fs::read_to_string(&page).unwrap(), "default|About Us");
+        assert!(new_content_file(&project.dir(), &String::from("posts/say-hi")).is_err());
+    }
+
+    #[test]
+    fn builds_an_archetype_from_the_files_in_the_section(){
+        let project: TempDir = TempDir::new("archetype-siblings");
+        project.write("layouts/default.liquid", "");
+        project.write("layouts/post.liquid", "");
+        project.write("posts/a.markdown", "---\nlayout: post\ndate: 2024-01-01\n---\nA");
+        project.write("posts/b.markdown", "---\nlayout: post\ndate: 2024-01-02\n---\nB");
+        let post: PathBuf = new_content_file(&project.dir(), &String::from("posts/new-one")).unwrap();
+        let expected: String = format!("---\nlayout: post\ntitle: \"New One\"\ndate: \"{}\"\n---\n", current_date());
+        assert_eq!(fs::read_to_string(&post).unwrap(), expected);
+        let note: PathBuf = new_content_file(&project.dir(), &String::from("notes/first")).unwrap();
+        assert_eq!(fs::read_to_string(&note).unwrap(), "---\nlayout: default\ntitle: \"First\"\n---\n");
+    }
+
+    #[test]
+    fn keeps_new_content_files_inside_the_project(){
+        let project: TempDir = TempDir::new("archetype-outside");
+        assert!(new_content_file(&project.dir(), &String::from("../outside")).is_err());
+        assert!(new_content_file(&project.dir(), &String::from("posts/../../outside")).is_err());
+        assert!(new_content_file(&project.dir(), &String::from("/tmp/outside")).is_err());
+        assert!(new_content_file(&project.dir(), &String::from("posts/!!!")).is_err());
+        assert!(!project.path.join("posts").exists());
+    }
+}
\ No newline at end of file
@@ -0,0 +1,588 @@
+/*
+Mandy by Alyx Shang.
+Licensed under the FSL v1.
+*/
+
+/// Importing the standard
+/// library for working with
+/// the filesystem.
+use std::fs;
+
+/// Importing the "Sha256"
+/// structure to hash the
+/// contents of files.
+use sha2::Sha256;
+
+/// Importing the "Digest"
+/// trait to hash the contents
+/// of files.
+use sha2::Digest;
+
+/// Importing the "PathBuf"
+/// structure from Rust's standard
+/// library.
+use std::path::PathBuf;
+
+/// Importing Mandy's error-handling
+/// structure to handle errors.
+use super::err::MandyErr;
+
+/// Importing the structure that
+/// holds what is known about
+/// an error.
+use super::err::ErrInfo;
+
+/// Importing the "SiteInfo"
+/// structure to read information
+/// gathered on a Mandy project.
+use super::units::SiteInfo;
+
+/// Importing the structure that
+/// holds the build cache of a
+/// Mandy project.
+use super::units::BuildCache;
+
+/// Importing the structure that
+/// holds what a single page was
+/// built from.
+use super::units::PageRecord;
+
+/// Importing Rust's standard API
+/// for working with maps.
+use std::collections::HashMap;
+
+/// Importing the structure to store
+/// information about content files
+/// in a Mandy project.
+use super::units::MandyContent;
+
+/// Importing the function to get the
+/// file stem of a path.
+use super::utils::extract_stem;
+
+/// Importing the function to find
+/// the namespace a data file is
+/// found in.
+use super::utils::get_data_namespace;
+
+/// Importing the function to
+/// find all files with a certain
+/// ending.
+use super::utils::find_files_with_ending;
+
+/// The name of the file the build
+/// cache is stored in, relative to
+/// the project directory.
+pub const CACHE_FILE: &str = ".mandy-cache";
+
+/// Returns the SHA-256 hash of the
+/// supplied bytes as a hexadecimal string.
+pub fn hash_bytes(subject: &[u8]) -> String {
+    let mut hasher: Sha256 = Sha256::new();
+    hasher.update(subject);
+    let digest = hasher.finalize();
+    let mut result: String = String::new();
+    for byte in digest.iter(){
+        result.push_str(&format!("{:02x}", byte));
+    }
+    result
+}
+
+/// Returns the path of the build cache
+/// of the Mandy project in the supplied
+/// directory.
+pub fn get_cache_path(dir: &String) -> PathBuf {
+    let mut cache_buf: PathBuf = PathBuf::new();
+    cache_buf.push(dir);
+    cache_buf.push(CACHE_FILE);
+    cache_buf
+}
+
+/// Reads the build cache of a Mandy project. A missing or unreadable
+/// cache yields an empty cache, so that everything is built again.
+pub fn read_build_cache(dir: &String) -> BuildCache {
+    let src: String = match fs::read_to_string(get_cache_path(dir)){
+        Ok(src) => src,
+        Err(_e) => return BuildCache::default()
+    };
+    serde_json::from_str::<BuildCache>(&src).unwrap_or_default()
+}
+
+/// Writes the build cache of a Mandy project to the project
+/// directory. Returns a "Result" type with an empty closure.
+pub fn write_build_cache(dir: &String, cache: &BuildCache) -> Result<(), MandyErr> {
+    let cache_buf: PathBuf = get_cache_path(dir);
+    let src: String = match serde_json::to_string(cache){
+        Ok(src) => src,
+        Err(e) => return Err::<(), MandyErr>(MandyErr::Io(Box::new(ErrInfo::from_source(e))).with_path(&cache_buf))
+    };
+    match fs::write(&cache_buf, src){
+        Ok(write_op) => Ok(write_op),
+        Err(e) => Err::<(), MandyErr>(MandyErr::Io(Box::new(ErrInfo::from_source(e))).with_path(&cache_buf))
+    }
+}
+
+/// Removes the build cache of a Mandy project if it exists.
+/// Returns a "Result" type with an empty closure.
+pub fn remove_build_cache(dir: &String) -> Result<(), MandyErr> {
+    let cache_buf: PathBuf = get_cache_path(dir);
+    if cache_buf.exists(){
+        match fs::remove_file(&cache_buf){
+            Ok(del_op) => Ok(del_op),
+            Err(e) => Err::<(), MandyErr>(MandyErr::Io(Box::new(ErrInfo::from_source(e))).with_path(&cache_buf))
+        }
+    }
+    else {
+        Ok(())
+    }
+}
+
+/// Finds the names of all partial templates that the supplied
+/// Liquid code includes or renders directly. Returns "None" if
+/// the code includes or renders a partial template whose name is
+/// not a string literal, as it may then use any partial template.
+pub fn find_partial_names(source: &str) -> Option<Vec<String>> {
+    let mut result: Vec<String> = Vec::new();
+    for tag in source.split("{%").skip(1){
+        let inner: &str = match tag.split_once("%}"){
+            Some((inner, _rest)) => inner,
+            None => continue
+        };
+        let mut words = inner.trim_matches('-').split_whitespace();
+        match words.next(){
+            Some("include") | Some("render") => {},
+            _ => continue
+        };
+        if let Some(name) = words.next() {
+            let literal: &str = name.trim_end_matches(',');
+            let is_quoted: bool = literal.len() >= 2 &&
+                ((literal.starts_with('"') && literal.ends_with('"')) ||
+                (literal.starts_with('\'') && literal.ends_with('\'')));
+            if !is_quoted {
+                return None;
+            }
+            let cleaned: String = literal[1..literal.len() - 1].to_string();
+            if !cleaned.is_empty() && !result.contains(&cleaned){
+                result.push(cleaned);
+            }
+        }
+    }
+    Some(result)
+}
+
+/// Finds the names of all data files that the supplied Liquid code reads
+/// via "data["name"]" or "data.name". Returns "None" if the code uses the
+/// "data" namespace in any other way, as it may then read any data file.
+pub fn find_data_names(source: &String) -> Option<Vec<String>> {
+    let mut result: Vec<String> = Vec::new();
+    let bytes: &[u8] = source.as_bytes();
+    let mut search_from: usize = 0;
+    while let Some(found) = source[search_from..].find("data") {
+        let start: usize = search_from + found;
+        let end: usize = start + 4;
+        search_from = end;
+        let is_word_start: bool = start == 0 ||
+            !(bytes[start - 1].is_ascii_alphanumeric() || bytes[start - 1] == b'_' || bytes[start - 1] == b'.');
+        let is_word_end: bool = end >= bytes.len() ||
+            !(bytes[end].is_ascii_alphanumeric() || bytes[end] == b'_');
+        if !is_word_start || !is_word_end {
+            continue;
+        }
+        let rest: &str = &source[end..];
+        let name: Option<String> = if rest.starts_with("[\"") || rest.starts_with("['") {
+            let quote: char = rest.chars().nth(1).unwrap_or('"');
+            rest[2..].find(quote).map(|close| rest[2..2 + close].to_string())
+        }
+        else if let Some(member) = rest.strip_prefix('.') {
+            let ident: String = member
+                .chars()
+                .take_while(|c| c.is_ascii_alphanumeric() || *c == '_' || *c == '-')
+                .collect();
+            if ident.is_empty() { None } else { Some(ident) }
+        }
+        else {
+            None
+        };
+        match name {
+            Some(name) => {
+                if !result.contains(&name){
+                    result.push(name);
+                }
+            },
+            None => return None
+        };
+    }
+    Some(result)
+}
+
+/// Works out which partial templates, which data files, and whether
+/// any loop content the layout with the supplied name uses. Partial
+/// templates including other partial templates are followed. If the
+/// layout may include any partial template, all partial templates are
+/// returned. The data files are "None" if the layout may read any data file.
+pub fn find_layout_dependencies(
+    layout_source: &str,
+    partials: &HashMap<String, String>
+) -> (Vec<String>, Option<Vec<String>>, bool) {
+    let mut used_partials: Vec<String> = Vec::new();
+    let mut sources: Vec<String> = vec![layout_source.to_string()];
+    let mut idx: usize = 0;
+    while idx < sources.len() {
+        let names: Vec<String> = match find_partial_names(&sources[idx]){
+            Some(names) => names,
+            None => {
+                for (name, partial_source) in partials {
+                    if !used_partials.contains(name){
+                        sources.push(partial_source.clone());
+                        used_partials.push(name.clone());
+                    }
+                }
+                Vec::new()
+            }
+        };
+        for name in names {
+            if !used_partials.contains(&name){
+                if let Some(partial_source) = partials.get(&name) {
+                    sources.push(partial_source.clone());
+                }
+                used_partials.push(name);
+            }
+        }
+        idx += 1;
+    }
+    let mut data_names: Option<Vec<String>> = Some(Vec::new());
+    let mut uses_loop_content: bool = false;
+    for source in &sources {
+        if source.contains("loop_content"){
+            uses_loop_content = true;
+        }
+        data_names = match (data_names, find_data_names(source)){
+            (Some(mut known), Some(found)) => {
+                for name in found {
+                    if !known.contains(&name){
+                        known.push(name);
+                    }
+                }
+                Some(known)
+            },
+            _ => None
+        };
+    }
+    used_partials.sort();
+    (used_partials, data_names, uses_loop_content)
+}
+
+/// Hashes every source file of a Mandy project: content files, layouts,
+/// partial templates, data files, and the configuration file with its overlay
+/// file. The keys of
+/// the returned map are the paths of the files. Partial templates are keyed
+/// by their name prefixed with "partials/". Returns a "Result" type.
+pub fn hash_source_files(data: &SiteInfo) -> Result<HashMap<String, String>, MandyErr> {
+    let mut result: HashMap<String, String> = HashMap::new();
+    let mut paths: Vec<PathBuf> = Vec::new();
+    paths.push(data.config.path.clone());
+    if let Some(overlay) = &data.config.overlay {
+        paths.push(overlay.clone());
+    }
+    for content_path in data.content_files.keys() {
+        if !data.generated_pages.contains_key(content_path){
+            paths.push(content_path.clone());
+        }
+    }
+    if let Some(data_files) = &data.data_files {
+        for data_file in data_files.values() {
+            paths.push(data_file.path.clone());
+        }
+    }
+    for path in paths {
+        let contents: Vec<u8> = match fs::read(&path){
+            Ok(contents) => contents,
+            Err(e) => return Err::<HashMap<String, String>, MandyErr>(MandyErr::Io(Box::new(ErrInfo::from_source(e))).with_path(&path))
+        };
+        result.insert(path.display().to_string(), hash_bytes(&contents));
+    }
+    for layout in &data.layouts {
+        result.insert(layout.path.display().to_string(), hash_bytes(layout.contents.as_bytes()));
+    }
+    for (name, contents) in &data.partials {
+        result.insert(format!("partials/{}", name), hash_bytes(contents.as_bytes()));
+    }
+    Ok(result)
+}
+
+/// Hashes all SASS files in the directory holding the stylesheet of a
+/// Mandy project into a single fingerprint. Returns "None" if the project
+/// has no stylesheet. Returns a "Result" type.
+pub fn hash_sass_files(data: &SiteInfo) -> Result<Option<String>, MandyErr> {
+    let sass_buf: PathBuf = match &data.sass_dir {
+        Some(sass_file) => match sass_file.parent(){
+            Some(sass_buf) => sass_buf.to_path_buf(),
+            None => return Ok(None)
+        },
+        None => return Ok(None)
+    };
+    let mut sass_files: Vec<PathBuf> = Vec::new();
+    for ext in ["scss", "sass", "css"] {
+        match find_files_with_ending(&sass_buf.display().to_string(), ext){
+            Ok(Some(mut found)) => sass_files.append(&mut found),
+            Ok(None) => {},
+            Err(e) => return Err::<Option<String>, MandyErr>(e)
+        };
+    }
+    sass_files.sort();
+    let mut combined: String = String::new();
+    for sass_file in sass_files {
+        let contents: Vec<u8> = match fs::read(&sass_file){
+            Ok(contents) => contents,
+            Err(e) => return Err::<Option<String>, MandyErr>(MandyErr::Io(Box::new(ErrInfo::from_source(e))).with_path(&sass_file))
+        };
+        combined.push_str(&format!("{}:{};", sass_file.display(), hash_bytes(&contents)));
+    }
+    Ok(Some(hash_bytes(combined.as_bytes())))
+}
+
+/// Works out what the supplied content file is built from and computes a
+/// fingerprint over all of it: the content file itself, its layout, the partial
+/// templates and data files the layout uses, any loop content the layout uses,
+/// the configuration file and its overlay file, the environment, and the base URL. Returns a "Result" type.
+pub fn build_page_record(
+    content_path: &PathBuf,
+    content: &MandyContent,
+    data: &SiteInfo,
+    hashes: &HashMap<String, String>,
+    baseurl: &String
+) -> Result<PageRecord, MandyErr> {
+    let mut layout_path: String = String::new();
+    let mut layout_source: String = String::new();
+    for layout in &data.layouts {
+        if layout.name == content.layout {
+            layout_path = layout.path.display().to_string();
+            layout_source = layout.contents.clone();
+            break;
+        }
+    }
+    let (partials, data_names, uses_loop_content): (Vec<String>, Option<Vec<String>>, bool) =
+        find_layout_dependencies(&layout_source, &data.partials);
+    let mut data_files: Vec<String> = Vec::new();
+    if let Some(site_data) = &data.data_files {
+        for (name, data_file) in site_data {
+            let is_used: bool = match &data_names {
+                Some(names) => names.contains(&get_data_namespace(name)),
+                None => true
+            };
+            if is_used {
+                data_files.push(data_file.path.display().to_string());
+            }
+        }
+    }
+    data_files.sort();
+    let source: String = content_path.display().to_string();
+    let source_key: String = match data.generated_pages.get(content_path){
+        Some(data_path) => data_path.display().to_string(),
+        None => source.clone()
+    };
+    let mut inputs: Vec<String> = Vec::new();
+    inputs.push(format!("mandy:{}", env!("CARGO_PKG_VERSION")));
+    inputs.push(format!("baseurl:{}", baseurl));
+    inputs.push(format!("environment:{}", data.config.environment));
+    inputs.push(format!("page:{}", content.path));
+    let config_path: String = data.config.path.display().to_string();
+    let mut keys: Vec<String> = vec![config_path, source_key, layout_path.clone()];
+    if let Some(overlay) = &data.config.overlay {
+        keys.push(overlay.display().to_string());
+    }
+    for partial in &partials {
+        keys.push(format!("partials/{}", partial));
+    }
+    for data_file in &data_files {
+        keys.push(data_file.clone());
+    }
+    if uses_loop_content {
+        if let Some(loop_content) = &data.loop_content {
+            let mut loop_paths: Vec<String> = Vec::new();
+            for (content_file_path, content_file) in &data.content_files {
+                let is_loop_content: bool = loop_content
+                    .values()
+                    .any(|items| items.iter().any(|item| item.path == content_file.path));
+                if is_loop_content {
+                    loop_paths.push(content_file_path.display().to_string());
+                }
+            }
+            loop_paths.sort();
+            keys.append(&mut loop_paths);
+        }
+    }
+    for key in &keys {
+        let hash: String = match hashes.get(key){
+            Some(hash) => hash.clone(),
+            None => String::from("missing")
+        };
+        inputs.push(format!("{}:{}", key, hash));
+    }
+    let layout_name: String = match extract_stem(&layout_path){
+        Ok(layout_name) => layout_name,
+        Err(_e) => content.layout.clone()
+    };
+    Ok(PageRecord{
+        source,
+        layout: layout_name,
+        partials,
+        data_files,
+        uses_loop_content,
+        fingerprint: hash_bytes(inputs.join("\n").as_bytes())
+    })
+}
+
+
+/// Tests for working out what
+/// pages are built from.
+#[cfg(test)]
+mod tests {
+
+    /// Importing the "PathBuf"
+    /// structure from Rust's standard
+    /// library.
+    use std::path::PathBuf;
+
+    /// Importing Rust's standard API
+    /// for working with maps.
+    use std::collections::HashMap;
+
+    /// Importing the "SiteInfo"
+    /// structure to describe a
+    /// Mandy project.
+    use crate::modules::units::SiteInfo;
+
+    /// Importing the structure that holds
+    /// the configuration file of a project.
+    use crate::modules::units::ConfigFile;
+
+    /// Importing the structure
+    /// that holds a layout.
+    use crate::modules::units::LayoutFile;
+
+    /// Importing the structure that
+    /// holds a page of a Mandy project.
+    use crate::modules::units::MandyContent;
+
+    /// Importing the structure
+    /// holding the options of a
+    /// Mandy project.
+    use crate::modules::units::MandyConfig;
+
+    /// Importing the enum describing
+    /// the format of a configuration file.
+    use crate::modules::units::UserDataFileType;
+
+    /// Importing the function to find
+    /// the partial templates Liquid
+    /// code includes.
+    use super::find_partial_names;
+
+    /// Importing the function to
+    /// fingerprint a page.
+    use super::build_page_record;
+
+    /// Builds a project with a single page using the
+    /// layout "page" with the supplied code and the
+    /// partial templates "head" and "footer".
+    fn site(layout_source: &str) -> (PathBuf, SiteInfo) {
+        let config: MandyConfig = serde_json::from_str("{\"title\": \"Site\"}").unwrap();
+        let content_path: PathBuf = PathBuf::from("site/index.markdown");
+        let content: MandyContent = MandyContent{
+            layout: String::from("page"),
+            params: HashMap::new(),
+            content: String::new(),
+            url: String::from("/index.html"),
+            path: String::from("site/dist/index.html")
+        };
+        let mut partials: HashMap<String, String> = HashMap::new();
+        partials.insert(String::from("head"), String::from("<head></head>"));
+        partials.insert(String::from("footer"), String::from("<footer></footer>"));
+        let data: SiteInfo = SiteInfo{
+            config: ConfigFile{
+                path: PathBuf::from("site/config.yml"),
+                file_type: UserDataFileType::YAML,
+                overlay: None,
+                environment: String::from("production"),
+                unknown_keys: Vec::new(),
+                contents: config
+            },
+            content_files: HashMap::from([(content_path.clone(), content)]),
+            generated_pages: HashMap::new(),
+            data_files: None,
+            loop_content: None,
+            layouts: vec![LayoutFile{
+                name: String::from("page"),
+                path: PathBuf::from("site/layouts/page.liquid"),
+                contents: layout_source.to_string()
+            }],
+            sass_dir: None,
+            partials,
+            staging: None
+        };
+        (content_path, data)
+    }
+
+    /// Returns the hashes of all sources of the project
+    /// from "site", with the supplied hash for the
+    /// partial template "footer".
+    fn hashes(footer: &str) -> HashMap<String, String> {
+        HashMap::from([
+            (String::from("site/config.yml"), String::from("config")),
+            (String::from("site/index.markdown"), String::from("index")),
+            (String::from("site/layouts/page.liquid"), String::from("page")),
+            (String::from("partials/head"), String::from("head")),
+            (String::from("partials/footer"), footer.to_string())
+        ])
+    }
+
+    /// Returns the fingerprint of the only page of the
+    /// supplied project with the supplied hashes.
+    fn fingerprint(site: &(PathBuf, SiteInfo), hashes: &HashMap<String, String>) -> String {
+        let (content_path, data) = site;
+        let content: &MandyContent = &data.content_files[content_path];
+        build_page_record(content_path, content, data, hashes, &String::new()).unwrap().fingerprint
+    }
+
+    #[test]
+    fn finds_literal_partial_names() {
+        let source: String = String::from("{% include \"head\" %}{%- render 'footer', x: 1 -%}{% include \"head\" %}");
+        assert_eq!(find_partial_names(&source), Some(vec![String::from("head"), String::from("footer")]));
+    }
+
+    #[test]
+    fn gives_up_on_dynamic_partial_names() {
+        assert_eq!(find_partial_names(&String::from("{% include name %}")), None);
+        assert_eq!(find_partial_names(&String::from("{% include \"head\" %}{% render page.partial %}")), None);
+    }
+
+    #[test]
+    fn fingerprints_are_stable() {
+        let project = site("{% include \"head\" %}");
+        assert_eq!(fingerprint(&project, &hashes("footer")), fingerprint(&project, &hashes("footer")));
+    }
+
+    #[test]
+    fn fingerprints_follow_included_partials_only() {
+        let project = site("{% include \"footer\" %}");
+        assert_ne!(fingerprint(&project, &hashes("footer")), fingerprint(&project, &hashes("changed")));
+        let project = site("{% include \"head\" %}");
+        assert_eq!(fingerprint(&project, &hashes("footer")), fingerprint(&project, &hashes("changed")));
+    }
+
+    #[test]
+    fn fingerprints_follow_all_partials_for_dynamic_includes() {
+        let project = site("{% assign name = \"head\" %}{% include name %}");
+        assert_ne!(fingerprint(&project, &hashes("footer")), fingerprint(&project, &hashes("changed")));
+    }
+
+    #[test]
+    fn fingerprints_follow_the_base_url() {
+        let (content_path, data) = site("{% include \"head\" %}");
+        let content: &MandyContent = &data.content_files[&content_path];
+        let local = build_page_record(&content_path, content, &data, &hashes("footer"), &String::new()).unwrap();
+        let remote = build_page_record(&content_path, content, &data, &hashes("footer"), &String::from("/blog")).unwrap();
+        assert_ne!(local.fingerprint, remote.fingerprint);
+    }
+}
\ No newline at end of file
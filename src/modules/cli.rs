@@ -11,6 +11,11 @@ use cliply::App;
 /// structure to handle errors.
 use super::err::MandyErr;
 
+/// Importing the structure that
+/// holds what is known about
+/// an error.
+use super::err::ErrInfo;
+
 /// Importing the function to 
 /// clean a compiled Mandy
 /// project.
@@ -20,25 +25,199 @@ use super::reset::clean_project;
 /// compile a Mandy project.
 use super::compile::compile_project;
 
+/// Importing the function to
+/// serve a Mandy project locally.
+use super::serve::serve_project;
+
+/// Importing the function to
+/// watch a Mandy project and
+/// recompile it on changes.
+use super::watch::watch_project;
+
+/// Importing the function to
+/// limit the number of threads
+/// used for rendering.
+use super::compile::set_render_jobs;
+
+/// Importing the function to
+/// create a new Mandy project.
+use super::scaffold::init_project;
+
+/// Importing the enum to
+/// describe the format of the
+/// configuration file.
+use super::units::UserDataFileType;
+
+/// Importing the function to
+/// create a new content file
+/// from an archetype.
+use super::archetypes::new_content_file;
+
+/// Importing the function to work
+/// out the environment to build for.
+use super::environment::resolve_environment;
+
+/// Importing the function to set
+/// the environment to build for.
+use super::environment::set_environment;
+
+/// Importing the name of the
+/// environment for a live site.
+use super::environment::PRODUCTION;
+
+/// Importing the name of the
+/// environment for working on a
+/// site locally.
+use super::environment::DEVELOPMENT;
+
+/// Importing the function to
+/// set the options for logging.
+use super::logger::set_log_options;
+
+/// Importing the function to
+/// parse the name of a format
+/// for log records.
+use super::logger::parse_log_format;
+
+/// Importing the structure
+/// holding the options for
+/// logging.
+use super::logger::LogOptions;
+
+/// Importing the enum describing
+/// how much Mandy reports.
+use super::logger::LogLevel;
+
+/// Importing the enum describing
+/// how log records are written.
+use super::logger::LogFormat;
+
 /// Mandy's light CLI. Returns a string with 
 /// feedback or an error in a "Result" type.
 pub fn cli() -> Result<String, MandyErr> {
 
     let mut mandy: App = App::new(
-        &"Mandy",
-        &"0.1.0",
-        &"Alyx Shang"
+        "Mandy",
+        "0.1.0",
+        "Alyx Shang"
+    );
+    mandy.add_arg(
+        "comps",
+        "  compile a Mandy project", 
+        &true
+    );
+    mandy.add_arg(
+        "reset", 
+        "  clean a compiled Mandy project", 
+        &true
+    );
+    mandy.add_arg(
+        "serve", 
+        "  compile and serve a Mandy project locally", 
+        &true
+    );
+    mandy.add_arg(
+        "watch", 
+        "  recompile a Mandy project on changes", 
+        &true
+    );
+    mandy.add_arg(
+        "init", 
+        "  create a new Mandy project", 
+        &true
+    );
+    mandy.add_arg(
+        "config-format", 
+        "  write the config of a new project as \"yaml\", \"json\", or \"toml\"", 
+        &true
+    );
+    mandy.add_arg(
+        "new", 
+        "  create a content file from an archetype", 
+        &true
     );
     mandy.add_arg(
-        &"comps",
-        &"  compile a Mandy project", 
+        "project", 
+        "  the Mandy project to create a content file in", 
         &true
     );
     mandy.add_arg(
-        &"reset", 
-        &"  clean a compiled Mandy project", 
+        "env", 
+        "  the environment to build for", 
+        &true
+    );
+    mandy.add_arg(
+        "jobs", 
+        "  limit the number of threads for rendering", 
+        &true
+    );
+    mandy.add_arg(
+        "quiet", 
+        "  only report errors", 
+        &false
+    );
+    mandy.add_arg(
+        "verbose", 
+        "  report every stage and every written file", 
+        &false
+    );
+    mandy.add_arg(
+        "log-format", 
+        "  write reports as \"text\" or \"json\"", 
         &true
     );
+    let level: LogLevel = match (mandy.arg_was_used("quiet"), mandy.arg_was_used("verbose")){
+        (true, true) => {
+            let e: String = "The \"--quiet\" and \"--verbose\" flags cannot be used together.".to_string();
+            return Err::<String,MandyErr>(MandyErr::Usage(Box::new(ErrInfo::new(&e.to_string()))))
+        },
+        (true, false) => LogLevel::Quiet,
+        (false, true) => LogLevel::Verbose,
+        (false, false) => LogLevel::Normal
+    };
+    let format: LogFormat = if mandy.arg_was_used("log-format"){
+        let format_str: String = match mandy.get_arg_data("log-format"){
+            Ok(format_str) => format_str,
+            Err(e) => return Err::<String,MandyErr>(MandyErr::Usage(Box::new(ErrInfo::new(&e.to_string()))))
+        };
+        parse_log_format(&format_str)?
+    }
+    else {
+        LogFormat::Text
+    };
+    let _log_op: () = set_log_options(&LogOptions{ level, format })?;
+    let builds: bool = mandy.arg_was_used("comps") || 
+        mandy.arg_was_used("reset") || 
+        mandy.arg_was_used("serve") || 
+        mandy.arg_was_used("watch");
+    if builds {
+        let env_flag: Option<String> = if mandy.arg_was_used("env"){
+            match mandy.get_arg_data("env"){
+                Ok(env_str) => Some(env_str),
+                Err(e) => return Err::<String,MandyErr>(MandyErr::Usage(Box::new(ErrInfo::new(&e.to_string()))))
+            }
+        }
+        else {
+            None
+        };
+        let default_env: &str = if mandy.arg_was_used("serve"){ DEVELOPMENT } else { PRODUCTION };
+        let environment: String = resolve_environment(&env_flag, default_env)?;
+        let _env_op: () = set_environment(&environment)?;
+    }
+    if mandy.arg_was_used("jobs"){
+        let jobs_str: String = match mandy.get_arg_data("jobs"){
+            Ok(jobs_str) => jobs_str,
+            Err(e) => return Err::<String,MandyErr>(MandyErr::Usage(Box::new(ErrInfo::new(&e.to_string()))))
+        };
+        let jobs: usize = match jobs_str.parse::<usize>(){
+            Ok(jobs) if jobs > 0 => jobs,
+            _ => {
+                let e: String = format!("The number of jobs must be a positive number, not \"{}\".", &jobs_str);
+                return Err::<String,MandyErr>(MandyErr::Usage(Box::new(ErrInfo::new(&e.to_string()))))
+            }
+        };
+        let _jobs_op: () = set_render_jobs(&jobs)?;
+    }
     if mandy.version_is(){
         Ok(mandy.version_info())
     }
@@ -48,27 +227,98 @@ pub fn cli() -> Result<String, MandyErr> {
     else if mandy.arg_was_used("comps"){
         let dir: String = match mandy.get_arg_data("comps"){
             Ok(dir) => dir,
-            Err(e) => return Err::<String,MandyErr>(MandyErr::new(&e.to_string()))
+            Err(e) => return Err::<String,MandyErr>(MandyErr::Usage(Box::new(ErrInfo::new(&e.to_string()))))
         };
         let del_op: String = match compile_project(&dir){
             Ok(_op) => format!("The Mandy project at \"{}\" has been compiled.", &dir),
-            Err(e) => return Err::<String,MandyErr>(MandyErr::new(&e.to_string()))
+            Err(e) => return Err::<String,MandyErr>(e)
         };
         Ok(del_op)
     }
     else if mandy.arg_was_used("reset"){
         let dir: String = match mandy.get_arg_data("reset"){
             Ok(dir) => dir,
-            Err(e) => return Err::<String,MandyErr>(MandyErr::new(&e.to_string()))
+            Err(e) => return Err::<String,MandyErr>(MandyErr::Usage(Box::new(ErrInfo::new(&e.to_string()))))
         };
         let del_op: String = match clean_project(&dir){
             Ok(_op) => format!("The Mandy project at \"{}\" has been cleaned.", &dir),
-            Err(e) => return Err::<String,MandyErr>(MandyErr::new(&e.to_string()))
+            Err(e) => return Err::<String,MandyErr>(e)
         };
         Ok(del_op)
     }
+    else if mandy.arg_was_used("serve"){
+        let dir: String = match mandy.get_arg_data("serve"){
+            Ok(dir) => dir,
+            Err(e) => return Err::<String,MandyErr>(MandyErr::Usage(Box::new(ErrInfo::new(&e.to_string()))))
+        };
+        let serve_op: String = match serve_project(&dir){
+            Ok(_op) => format!("The server for the Mandy project at \"{}\" has stopped.", &dir),
+            Err(e) => return Err::<String,MandyErr>(e)
+        };
+        Ok(serve_op)
+    }
+    else if mandy.arg_was_used("watch"){
+        let dir: String = match mandy.get_arg_data("watch"){
+            Ok(dir) => dir,
+            Err(e) => return Err::<String,MandyErr>(MandyErr::Usage(Box::new(ErrInfo::new(&e.to_string()))))
+        };
+        let watch_op: String = match watch_project(&dir){
+            Ok(_op) => format!("Stopped watching the Mandy project at \"{}\".", &dir),
+            Err(e) => return Err::<String,MandyErr>(e)
+        };
+        Ok(watch_op)
+    }
+    else if mandy.arg_was_used("init"){
+        let dir: String = match mandy.get_arg_data("init"){
+            Ok(dir) => dir,
+            Err(e) => return Err::<String,MandyErr>(MandyErr::Usage(Box::new(ErrInfo::new(&e.to_string()))))
+        };
+        let config_type: UserDataFileType = if mandy.arg_was_used("config-format"){
+            let format_str: String = match mandy.get_arg_data("config-format"){
+                Ok(format_str) => format_str,
+                Err(e) => return Err::<String,MandyErr>(MandyErr::Usage(Box::new(ErrInfo::new(&e.to_string()))))
+            };
+            match format_str.as_str(){
+                "yaml" | "yml" => UserDataFileType::YAML,
+                "json" => UserDataFileType::JSON,
+                "toml" => UserDataFileType::TOML,
+                _ => {
+                    let e: String = format!("The config format must be \"yaml\", \"json\", or \"toml\", not \"{}\".", &format_str);
+                    return Err::<String,MandyErr>(MandyErr::Usage(Box::new(ErrInfo::new(&e.to_string()))))
+                }
+            }
+        }
+        else {
+            UserDataFileType::YAML
+        };
+        let init_op: String = match init_project(&dir, &config_type){
+            Ok(_op) => format!("A new Mandy project has been created at \"{}\". Compile it with \"mandy comps {}\".", &dir, &dir),
+            Err(e) => return Err::<String,MandyErr>(e)
+        };
+        Ok(init_op)
+    }
+    else if mandy.arg_was_used("new"){
+        let target: String = match mandy.get_arg_data("new"){
+            Ok(target) => target,
+            Err(e) => return Err::<String,MandyErr>(MandyErr::Usage(Box::new(ErrInfo::new(&e.to_string()))))
+        };
+        let dir: String = if mandy.arg_was_used("project"){
+            match mandy.get_arg_data("project"){
+                Ok(dir) => dir,
+                Err(e) => return Err::<String,MandyErr>(MandyErr::Usage(Box::new(ErrInfo::new(&e.to_string()))))
+            }
+        }
+        else {
+            String::from(".")
+        };
+        let new_op: String = match new_content_file(&dir, &target){
+            Ok(file_buf) => format!("The content file \"{}\" has been created.", file_buf.display()),
+            Err(e) => return Err::<String,MandyErr>(e)
+        };
+        Ok(new_op)
+    }
     else {
-        Err::<String,MandyErr>(MandyErr::new(&format!("{}", mandy.help_info())))
+        Err::<String,MandyErr>(MandyErr::Usage(Box::new(ErrInfo::new(&mandy.help_info().to_string()))))
     }
     
 }
\ No newline at end of file
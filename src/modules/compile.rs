@@ -16,16 +16,16 @@ use coutils;
 /// for working with the filesystem.
 use std::fs;
 
-/// Importing the function to read
-/// environment variables from
-/// Rust's standard library.
-use std::env::var;
-
 /// Importing the "PathBuf"
 /// structure from Rust's standard
 /// library.
 use std::path::PathBuf;
 
+/// Importing the "Path"
+/// structure from Rust's standard
+/// library.
+use std::path::Path;
+
 /// Importing the function
 /// to generate files for SEO
 /// in a compiled Mandy project.
@@ -35,6 +35,11 @@ use super::extras::seo;
 /// structure to handle errors.
 use super::err::MandyErr;
 
+/// Importing the structure that
+/// holds what is known about
+/// an error.
+use super::err::ErrInfo;
+
 /// Importing the "PathInfo"
 /// structure to store information
 /// about paths.
@@ -45,14 +50,6 @@ use super::units::PathInfo;
 /// about the Mandy project.
 use super::units::SiteInfo;
 
-/// Importing the "Direction"
-/// enum to specify in which
-/// direction an insertion
-/// into an instance of the
-/// "PathBuf" structure should take
-/// place.
-use super::units::Direction;
-
 /// Importing the "LayoutFile"
 /// structure to save information
 /// about detected files with
@@ -63,11 +60,26 @@ use super::units::LayoutFile;
 /// clean specified user data.
 use super::utils::clean_data;
 
+/// Importing the structure that holds all
+/// information on a configuration file in a
+/// a Mandy project.
+use super::units::ConfigFile;
+
+/// Importing the function
+/// to read a Mandy project's 
+/// configuration settings.
+use super::gather::read_config;
+
 /// Importing the structure
 /// from Rust's standard library
 /// to work with maps.
 use std::collections::HashMap;
 
+/// Importing the "Value"
+/// enum to hold the values
+/// of data files.
+use liquid::model::Value;
+
 /// Importing the structure that supplies
 /// the context needed for compiling
 /// HTML templates into HTML.
@@ -78,20 +90,6 @@ use super::units::SiteContext;
 /// in a Mandy project.
 use super::units::MandyContent;
 
-/// Importing the function to split an
-/// instance of the "PathBuf" structure.
-use super::utils::split_path_buf;
-
-/// Importing the function to 
-/// find all files containing SASS
-/// code in a Mandy project.
-use super::gather::find_sass_files;
-
-/// Importing the function to split an
-/// instance of the "PathBuf" structure
-/// at the given item.
-use super::utils::split_buf_at_item;
-
 /// Importing the function to get the full parent
 /// path from an instance of the "PathBuf" structure.
 use super::utils::get_path_from_buf;
@@ -100,230 +98,654 @@ use super::utils::get_path_from_buf;
 /// by the supplied name.
 use super::utils::get_layout_by_name;
 
-/// Importing the function to process HTML
-/// templates.
-use super::processors::process_liquid;
+/// Importing the function to retrieve
+/// the base URL for the current environment.
+use super::utils::get_env_baseurl;
+
+/// Importing the function to copy
+/// a directory and its contents.
+use super::utils::copy_directory;
+
+/// Importing the function to build
+/// a parser for Liquid templates.
+use super::processors::build_parser;
+
+/// Importing the function to turn
+/// errors from the "liquid" crate
+/// into diagnostics.
+use super::diagnostics::liquid_error;
+
+/// Importing the function to return
+/// the paths and the code of all
+/// partial templates.
+use super::diagnostics::partial_sources;
+
+/// Importing the function to return
+/// the paths and the code of a layout
+/// and all partial templates.
+use super::diagnostics::layout_sources;
+
+/// Importing the function to render
+/// parsed Liquid templates.
+use super::processors::render_template;
+
+/// Importing the structure that holds
+/// everything prepared once for rendering
+/// pages.
+use super::units::RenderInfo;
+
+/// Importing the "Parser"
+/// structure from the "liquid"
+/// crate to parse templates.
+use liquid::Parser;
+
+/// Importing the "Template"
+/// structure from the "liquid"
+/// crate to render templates.
+use liquid::Template;
+
+/// Importing the trait to iterate
+/// over vectors in parallel.
+use rayon::prelude::*;
+
+/// Importing the structure to
+/// limit the number of threads
+/// used for rendering.
+use rayon::ThreadPoolBuilder;
+
+/// Importing the structure that
+/// holds the build cache of a
+/// Mandy project.
+use super::units::BuildCache;
+
+/// Importing the structure that
+/// holds what a single page was
+/// built from.
+use super::units::PageRecord;
+
+/// Importing the function to read
+/// the build cache of a Mandy project.
+use super::cache::read_build_cache;
+
+/// Importing the function to write
+/// the build cache of a Mandy project.
+use super::cache::write_build_cache;
+
+/// Importing the function to hash
+/// all source files of a Mandy project.
+use super::cache::hash_source_files;
+
+/// Importing the function to hash
+/// all SASS files of a Mandy project.
+use super::cache::hash_sass_files;
+
+/// Importing the function to work out
+/// what a single page is built from.
+use super::cache::build_page_record;
+
+/// Importing the function to compare
+/// paths from options without their
+/// "." components.
+use super::config::normalize_project_path;
 
 /// Importing the function that 
 /// gathers information about the Mandy project
 /// in the supplied directory.
 use super::gather::gather_project_data;
 
+/// Importing the "Instant"
+/// structure to time the stages
+/// of a build.
+use std::time::Instant;
+
+/// Importing the function to report
+/// a finished stage of a build.
+use super::logger::log_stage;
+
+/// Importing the function to report
+/// a written file.
+use super::logger::log_written;
+
+/// Importing the function to report
+/// a problem that does not stop a build.
+use super::logger::log_warning;
+
 /// Importing the function to find
-/// all loop content files.
-use super::gather::find_loop_content_files;
+/// problems that do not stop a build.
+use super::warnings::find_warnings;
+
+/// Importing the function to build
+/// the "page" variable of a
+/// generated page.
+use super::generators::get_generated_page_object;
 
-/// Importing the function to insert an entity
-/// into an instance of the "PathBuf" structure
-/// at a given item.
-use super::utils::insert_entity_into_buf_at_item;
+/// Importing the "Object"
+/// type to hold the "page"
+/// variable of a generated page.
+use liquid::model::Object;
 
-/// Compiles a Mandy project. Returns a string if the operation succeeds.
+/// Compiles a Mandy project. The base URL is chosen
+/// from the environment built for. Returns a string if the operation succeeds.
 /// If the operation fails, an error is returned.
 pub fn compile_project(dir: &String) -> Result<(), MandyErr>{
-    if coutils::dir_is(&dir){
-        let data: SiteInfo = match gather_project_data(dir){
-            Ok(data) => data,
-            Err(e) => return Err::<(), MandyErr>(MandyErr::new(&e.to_string()))
-        };
-        let dist_dir: &String = &data.config.contents.dist_dir;
-        let mut dist_dir_buf: PathBuf = PathBuf::new();
-        dist_dir_buf.push(dir);
-        dist_dir_buf.push(dist_dir);
-        let _create_dist_dir_op: () = match coutils::create_directory(&dist_dir_buf.display().to_string()){
-            Ok(_create_dist_dir_op) => _create_dist_dir_op,
-            Err(e) => return Err::<(), MandyErr>(MandyErr::new(&e.to_string()))
-        };
-        let _compile_md: () = match compile_markdown_files(dir){
-            Ok(_compile_md) => _compile_md,
-            Err(e) => return Err::<(), MandyErr>(MandyErr::new(&e.to_string()))
-        };
-        let _copy_files: () = match copy_files(dir){
-            Ok(_copy_files) => _copy_files,
-            Err(e) => return Err::<(), MandyErr>(MandyErr::new(&e.to_string()))
-        };
-        let _compile_sass: () = match compile_sass(dir){
-            Ok(_compile_sass) => _compile_sass,
-            Err(e) => return Err::<(), MandyErr>(MandyErr::new(&e.to_string()))
-        };
-        let _compile_loop_content: () = match compile_loop_content_files(dir){
-            Ok(_compile_loop_content) => _compile_loop_content,
-            Err(e) => return Err::<(), MandyErr>(MandyErr::new(&e.to_string()))
-        };
-        let seo_op: () = match seo(dir, &data.content_files){
-            Ok(seo) => seo,
-            Err(e) => return Err::<(), MandyErr>(MandyErr::new(&e.to_string()))
-        };
-        Ok(seo_op)
+    let config: ConfigFile = read_config(dir)?;
+    let env_baseurl: String = get_env_baseurl(&config.contents)?;
+    compile_project_with_baseurl(dir, &env_baseurl)
+}
+
+/// Compiles a Mandy project using the supplied base URL for
+/// the "baseurl" template variable. The project is gathered once
+/// and the result is passed to every stage of compilation. Returns an 
+/// empty closure if the operation succeeds. If the operation fails, 
+/// an error is returned.
+pub fn compile_project_with_baseurl(dir: &String, baseurl: &String) -> Result<(), MandyErr>{
+    if coutils::dir_is(dir){
+        let started: Instant = Instant::now();
+        let data: SiteInfo = gather_project_data(dir)?;
+        log_stage("gather project", &started);
+        compile_site_info(dir, &data, baseurl)
     }
     else {
         let e: String = format!("The directory \"{}\" does not exist.", dir);
-        return Err::<(), MandyErr>(MandyErr::new(&e.to_string()));
+        Err::<(), MandyErr>(MandyErr::Io(Box::new(ErrInfo::new(&e.to_string()))).with_path(&PathBuf::from(dir)))
     }
 }
 
-/// Compiles all content files in a Mandy project written in Markdown,
-/// that a Mandy project has. Returns a "Result" type with an empty closure.
-pub fn compile_markdown_files(dir: &String) -> Result<(), MandyErr>{
-    let data: SiteInfo = match gather_project_data(dir){
-        Ok(data) => data,
-        Err(e) => return Err::<(), MandyErr>(MandyErr::new(&e.to_string()))
-    };
+/// Compiles a Mandy project from information that has already been
+/// gathered on it. The supplied base URL is used for the "baseurl" 
+/// template variable. The project is compiled into an empty staging directory
+/// next to the directory for the compiled project. Only once every stage has
+/// succeeded is the staging
+/// directory swapped in and the build cache written, so a failed build leaves
+/// the last good build untouched. Problems that do not stop the build are
+/// reported as warnings before it starts. Returns an empty closure if the operation
+/// succeeds. If the operation fails, an error is returned.
+pub fn compile_site_info(dir: &String, data: &SiteInfo, baseurl: &String) -> Result<(), MandyErr>{
     let dist_dir: &String = &data.config.contents.dist_dir;
-    let content_files: HashMap<PathBuf, MandyContent> = data.content_files;
-    let baseurl_var: String = match var("MANDY_ENV"){
-        Ok(baseurl_var) => baseurl_var,
-        Err(e) => return Err::<(), MandyErr>(MandyErr::new(&e.to_string()))
+    let staging_dir: String = get_staging_dir(dist_dir);
+    for warning in find_warnings(dir, data) {
+        log_warning(&warning);
+    }
+    let started: Instant = Instant::now();
+    let _prepare_op: () = prepare_staging_dir(dir, &staging_dir)?;
+    log_stage("prepare staging directory", &started);
+    let staged: SiteInfo = stage_site_info(dir, data, &staging_dir)?;
+    let mut staging_buf: PathBuf = PathBuf::new();
+    staging_buf.push(dir);
+    staging_buf.push(&staging_dir);
+    let mut dist_buf: PathBuf = PathBuf::new();
+    dist_buf.push(dir);
+    dist_buf.push(dist_dir);
+    let compiled: Result<BuildCache, MandyErr> = compile_stages(dir, &staged, &dist_buf, baseurl);
+    let new_cache: BuildCache = match compiled {
+        Ok(new_cache) => new_cache,
+        Err(e) => {
+            let _del_op = fs::remove_dir_all(&staging_buf);
+            return Err::<(), MandyErr>(e);
+        }
     };
-    let env_baseurl: String;
-    if baseurl_var == "production"{
-        env_baseurl = data.config.contents.prod_url.clone();
+    let started: Instant = Instant::now();
+    let _swap_op: () = swap_staging_dir(dir, dist_dir, &staging_dir)?;
+    log_stage("swap in staging directory", &started);
+    write_build_cache(dir, &new_cache)
+}
+
+/// Returns the name of the staging directory for the supplied directory
+/// for the compiled project. The staging directory is a hidden directory
+/// next to the directory for the compiled project.
+pub fn get_staging_dir(dist_dir: &String) -> String {
+    let dist_buf: PathBuf = normalize_project_path(dist_dir);
+    let name: String = match dist_buf.file_name(){
+        Some(name) => name.to_string_lossy().to_string(),
+        None => dist_dir.clone()
+    };
+    dist_buf.with_file_name(format!(".{}-staging", name)).display().to_string()
+}
+
+/// Returns the name of the directory the last build is moved to while
+/// the staging directory is swapped in.
+pub fn get_previous_dir(dist_dir: &String) -> String {
+    let dist_buf: PathBuf = normalize_project_path(dist_dir);
+    let name: String = match dist_buf.file_name(){
+        Some(name) => name.to_string_lossy().to_string(),
+        None => dist_dir.clone()
+    };
+    dist_buf.with_file_name(format!(".{}-previous", name)).display().to_string()
+}
+
+/// Creates a fresh, empty staging directory. Any staging directory left
+/// behind by an earlier build is removed first. Returns a "Result" type
+/// with an empty closure.
+pub fn prepare_staging_dir(dir: &String, staging_dir: &String) -> Result<(), MandyErr>{
+    let mut staging_buf: PathBuf = PathBuf::new();
+    staging_buf.push(dir);
+    staging_buf.push(staging_dir);
+    if staging_buf.exists(){
+        let _del_op: () = match fs::remove_dir_all(&staging_buf){
+            Ok(_del_op) => _del_op,
+            Err(e) => return Err::<(), MandyErr>(MandyErr::Io(Box::new(ErrInfo::from_source(e))).with_path(&staging_buf))
+        };
     }
-    else if baseurl_var == "development"{
-        env_baseurl = data.config.contents.dev_url.clone();
+    match fs::create_dir_all(&staging_buf){
+        Ok(create_op) => Ok(create_op),
+        Err(e) => Err::<(), MandyErr>(MandyErr::Io(Box::new(ErrInfo::from_source(e))).with_path(&staging_buf))
     }
-    else {
-        let e: String = format!("The environment variable \"$MANDY_ENV\" must be set to either \"production\" or \"development\"!");
-        return Err::<(), MandyErr>(MandyErr::new(&e.to_string()));
+}
+
+/// Carries the file at the supplied path inside the staging directory over
+/// from the last build in the supplied directory for the compiled project.
+/// The file is hard-linked where possible and copied otherwise. Files shared
+/// with the last build are never written to, as every stage removes a file
+/// before replacing it. Returns whether the last build had the file. A
+/// "Result" type is returned.
+pub fn reuse_output(staged_path: &PathBuf, staging_buf: &PathBuf, dist_buf: &Path) -> Result<bool, MandyErr>{
+    let previous_buf: PathBuf = match staged_path.strip_prefix(staging_buf){
+        Ok(relative) => dist_buf.join(relative),
+        Err(_e) => return Ok(false)
+    };
+    if !previous_buf.is_file(){
+        return Ok(false);
+    }
+    if let Some(parent) = staged_path.parent() {
+        let _create_op: () = match fs::create_dir_all(parent){
+            Ok(_create_op) => _create_op,
+            Err(e) => return Err::<bool, MandyErr>(MandyErr::Io(Box::new(ErrInfo::from_source(e))).with_path(staged_path))
+        };
+    }
+    match fs::hard_link(&previous_buf, staged_path){
+        Ok(_link_op) => Ok(true),
+        Err(_e) => match fs::copy(&previous_buf, staged_path){
+            Ok(_copy_op) => Ok(true),
+            Err(e) => Err::<bool, MandyErr>(MandyErr::Io(Box::new(ErrInfo::from_source(e))).with_path(staged_path))
+        }
     }
-    for (content_file_path,content) in content_files {
-        let path_info: PathInfo = match get_path_from_buf(&content_file_path){
-            Ok(path_info) => path_info,
-            Err(e) => return Err::<(), MandyErr>(MandyErr::new(&e.to_string()))
+}
+
+/// Returns a copy of the supplied information on a Mandy project that
+/// writes to the supplied staging directory instead of the directory for
+/// the compiled project. Returns a "Result" type.
+pub fn stage_site_info(dir: &String, data: &SiteInfo, staging_dir: &String) -> Result<SiteInfo, MandyErr>{
+    let mut dist_buf: PathBuf = PathBuf::new();
+    dist_buf.push(dir);
+    dist_buf.push(&data.config.contents.dist_dir);
+    let mut staging_buf: PathBuf = PathBuf::new();
+    staging_buf.push(dir);
+    staging_buf.push(staging_dir);
+    let restage = |content: &MandyContent| -> Result<MandyContent, MandyErr> {
+        let relative: PathBuf = match PathBuf::from(&content.path).strip_prefix(&dist_buf){
+            Ok(relative) => relative.to_path_buf(),
+            Err(e) => return Err::<MandyContent, MandyErr>(MandyErr::Other(Box::new(ErrInfo::from_source(e))))
         };
-        let with_dist: PathBuf = match insert_entity_into_buf_at_item(&path_info.path, dist_dir, dir, &Direction::After){
-            Ok(with_dist) => with_dist,
-            Err(e) => return Err::<(), MandyErr>(MandyErr::new(&e.to_string()))
+        let mut staged_content: MandyContent = content.clone();
+        staged_content.path = staging_buf.join(relative).display().to_string();
+        Ok(staged_content)
+    };
+    let mut staged: SiteInfo = data.clone();
+    staged.config.contents.dist_dir = staging_dir.clone();
+    staged.staging = Some((staging_buf.clone(), dist_buf.clone()));
+    for (_content_file_path, content) in staged.content_files.iter_mut() {
+        *content = restage(content)?;
+    }
+    if let Some(loop_content) = staged.loop_content.as_mut() {
+        for (_loop_dir, contents) in loop_content.iter_mut() {
+            for content in contents.iter_mut() {
+                *content = restage(content)?;
+            }
+        }
+    }
+    Ok(staged)
+}
+
+/// Replaces the directory for the compiled project with the staging
+/// directory. The last build is only removed once the staging directory
+/// is in place and is restored if the swap fails. Returns a "Result" type
+/// with an empty closure.
+pub fn swap_staging_dir(dir: &String, dist_dir: &String, staging_dir: &String) -> Result<(), MandyErr>{
+    let mut dist_buf: PathBuf = PathBuf::new();
+    dist_buf.push(dir);
+    dist_buf.push(dist_dir);
+    let mut staging_buf: PathBuf = PathBuf::new();
+    staging_buf.push(dir);
+    staging_buf.push(staging_dir);
+    let mut previous_buf: PathBuf = PathBuf::new();
+    previous_buf.push(dir);
+    previous_buf.push(get_previous_dir(dist_dir));
+    if previous_buf.exists(){
+        let _del_op: () = match fs::remove_dir_all(&previous_buf){
+            Ok(_del_op) => _del_op,
+            Err(e) => return Err::<(), MandyErr>(MandyErr::Io(Box::new(ErrInfo::from_source(e))).with_path(&previous_buf))
         };
-        let mut subdirs: PathBuf = match split_buf_at_item(&with_dist, dir, &false){
-            Ok(subdirs) => subdirs,
-            Err(e) => return Err::<(), MandyErr>(MandyErr::new(&e.to_string()))
+    }
+    let had_previous: bool = dist_buf.exists();
+    if had_previous {
+        let _move_op: () = match fs::rename(&dist_buf, &previous_buf){
+            Ok(_move_op) => _move_op,
+            Err(e) => return Err::<(), MandyErr>(MandyErr::Io(Box::new(ErrInfo::from_source(e))).with_path(&dist_buf))
         };
-        if &path_info.path.display().to_string() == dir{}
-        else {
-            subdirs.push(path_info.file);
+    }
+    match fs::rename(&staging_buf, &dist_buf){
+        Ok(_swap_op) => {},
+        Err(e) => {
+            if had_previous {
+                let _restore_op = fs::rename(&previous_buf, &dist_buf);
+            }
+            return Err::<(), MandyErr>(MandyErr::Io(Box::new(ErrInfo::from_source(e))).with_path(&staging_buf));
         }
-        let subdirs_items: Vec<String> = match split_path_buf(&subdirs){
-            Ok(subdirs_items) => subdirs_items,
-            Err(e) => return Err::<(), MandyErr>(MandyErr::new(&e.to_string()))
+    };
+    if had_previous {
+        match fs::remove_dir_all(&previous_buf){
+            Ok(del_op) => Ok(del_op),
+            Err(e) => Err::<(), MandyErr>(MandyErr::Io(Box::new(ErrInfo::from_source(e))).with_path(&previous_buf))
+        }
+    }
+    else {
+        Ok(())
+    }
+}
+
+/// Runs every stage of compiling a Mandy project from information already
+/// gathered on it and returns the new build cache. Rendering pages, copying
+/// files, compiling SASS, and writing the SEO files all run even if one of
+/// them fails, and all errors are returned together. Only pages whose sources
+/// changed since the last build are rendered again and the SASS files are only
+/// compiled if a file in the "sass" directory changed. Everything else is carried over from the
+/// last build in the supplied directory for the compiled project. Returns a
+/// "Result" type.
+pub fn compile_stages(dir: &String, data: &SiteInfo, dist_buf: &Path, baseurl: &String) -> Result<BuildCache, MandyErr>{
+    let mut dist_dir_buf: PathBuf = PathBuf::new();
+    dist_dir_buf.push(dir);
+    dist_dir_buf.push(&data.config.contents.dist_dir);
+    let started: Instant = Instant::now();
+    let old_cache: BuildCache = read_build_cache(dir);
+    let hashes: HashMap<String, String> = hash_source_files(data)?;
+    let mut errors: Vec<MandyErr> = Vec::new();
+    let mut pages: HashMap<String, PageRecord> = HashMap::new();
+    let mut changed: Vec<(&PathBuf, &MandyContent)> = Vec::new();
+    for (content_file_path, content) in &data.content_files {
+        let record: PageRecord = match build_page_record(content_file_path, content, data, &hashes, baseurl){
+            Ok(record) => record,
+            Err(e) => {
+                errors.push(e);
+                continue;
+            }
         };
-        let mut subdirs_items_buf: PathBuf = PathBuf::new();
-        for subdir_item in subdirs_items.clone(){
-            subdirs_items_buf.push(subdir_item);
-            if subdirs_items_buf.exists(){}
-            else {
-                let _create_op: () = match coutils::create_directory(&subdirs_items_buf.display().to_string()){
-                    Ok(_create_op) => _create_op,
-                    Err(e) => return Err::<(), MandyErr>(MandyErr::new(&e.to_string()))
-                };
+        let is_unchanged: bool = match old_cache.pages.get(&record.source){
+            Some(old_record) => old_record.fingerprint == record.fingerprint,
+            None => false
+        };
+        let is_fresh: bool = if is_unchanged {
+            match reuse_output(&PathBuf::from(&content.path), &dist_dir_buf, dist_buf){
+                Ok(is_fresh) => is_fresh,
+                Err(e) => {
+                    errors.push(e);
+                    false
+                }
             }
         }
-        subdirs_items_buf.push("index.html");
-        if subdirs_items_buf.exists(){
-            let e: String = format!("Filesystem at \"{}\" already exists.", &subdirs_items_buf.display().to_string());
-            return Err::<(), MandyErr>(MandyErr::new(&e.to_string()))
+        else {
+            false
+        };
+        if !is_fresh {
+            changed.push((content_file_path, content));
+        }
+        pages.insert(record.source.clone(), record);
+    }
+    log_stage("hash sources", &started);
+    let started: Instant = Instant::now();
+    match compile_content_files(data, &changed, baseurl){
+        Ok(_compile_md) => {},
+        Err(e) => errors.push(e)
+    };
+    log_stage("render pages", &started);
+    let started: Instant = Instant::now();
+    match copy_files(dir, data){
+        Ok(_copy_files) => {},
+        Err(e) => errors.push(e)
+    };
+    log_stage("copy files", &started);
+    let sass_hash: Option<String> = match hash_sass_files(data){
+        Ok(sass_hash) => sass_hash,
+        Err(e) => {
+            errors.push(e);
+            None
         }
+    };
+    let mut css_file_buf: PathBuf = dist_dir_buf.clone();
+    css_file_buf.push("css");
+    css_file_buf.push("index.css");
+    let is_sass_unchanged: bool = sass_hash.is_some() && sass_hash == old_cache.sass;
+    let is_css_fresh: bool = if is_sass_unchanged {
+        match reuse_output(&css_file_buf, &dist_dir_buf, dist_buf){
+            Ok(is_css_fresh) => is_css_fresh,
+            Err(e) => {
+                errors.push(e);
+                false
+            }
+        }
+    }
+    else {
+        false
+    };
+    if !is_css_fresh {
+        let started: Instant = Instant::now();
+        match compile_sass(dir, data){
+            Ok(_compile_sass) => {},
+            Err(e) => errors.push(e)
+        };
+        log_stage("compile sass", &started);
+    }
+    let started: Instant = Instant::now();
+    match seo(dir, data){
+        Ok(_seo_op) => {},
+        Err(e) => errors.push(e)
+    };
+    log_stage("write seo files", &started);
+    if !errors.is_empty(){
+        return Err::<BuildCache, MandyErr>(MandyErr::from_errors(errors));
+    }
+    let _compile_loop_content: () = compile_loop_content_files(data)?;
+    Ok(BuildCache {
+        files: hashes,
+        pages,
+        sass: sass_hash
+    })
+}
+
+/// Sets the number of threads used for rendering pages. This
+/// function can only be called once and must be called before
+/// anything is compiled. Returns a "Result" type with an empty closure.
+pub fn set_render_jobs(jobs: &usize) -> Result<(), MandyErr>{
+    match ThreadPoolBuilder::new().num_threads(*jobs).build_global(){
+        Ok(_pool_op) => Ok(()),
+        Err(e) => Err::<(), MandyErr>(MandyErr::Usage(Box::new(ErrInfo::from_source(e))))
+    }
+}
+
+/// Compiles all content files in a Mandy project written in Markdown,
+/// from information already gathered on the project. The supplied base URL is made available
+/// to templates as "baseurl". Existing HTML files are overwritten.
+/// Returns a "Result" type with an empty closure.
+pub fn compile_markdown_files(data: &SiteInfo, baseurl: &String) -> Result<(), MandyErr>{
+    let mut contents: Vec<(&PathBuf, &MandyContent)> = Vec::new();
+    for (content_file_path,content) in &data.content_files {
+        contents.push((content_file_path, content));
+    }
+    compile_content_files(data, &contents, baseurl)
+}
+
+/// Prepares everything needed for rendering the supplied content files once:
+/// the parser with all partial templates, every layout these content files use,
+/// the code of these layouts and the partial templates for pointing to failing
+/// code, and the project's data. Layouts that cannot be found or parsed are left out
+/// and their errors are returned next to the prepared information. Returns a
+/// "Result" type.
+pub fn prepare_render_info(data: &SiteInfo, contents: &Vec<&MandyContent>) -> Result<(RenderInfo, Vec<MandyErr>), MandyErr>{
+    let parser: Parser = build_parser(&data.partials, &partial_sources(data))?;
+    let mut templates: HashMap<String, Template> = HashMap::new();
+    let mut sources: HashMap<String, Vec<(PathBuf, String)>> = HashMap::new();
+    let mut failed: Vec<String> = Vec::new();
+    let mut errors: Vec<MandyErr> = Vec::new();
+    for content in contents {
+        if templates.contains_key(&content.layout) || failed.contains(&content.layout){}
         else {
-            let _create_html_op: () = match coutils::create_file(&subdirs_items_buf.display().to_string()){
-                Ok(_create_html_op) => _create_html_op,
-                Err(e) => return Err::<(), MandyErr>(MandyErr::new(&e.to_string()))
-            };
             let liquid_template: LayoutFile = match get_layout_by_name(&content.layout, &data.layouts){
                 Ok(liquid_template) => liquid_template,
-                Err(e) => return Err::<(), MandyErr>(MandyErr::new(&e.to_string()))
+                Err(e) => {
+                    failed.push(content.layout.clone());
+                    errors.push(e);
+                    continue;
+                }
             };
-            let compile_ctx: SiteContext;
-            match &data.loop_content{
-                Some(loop_content) => {
-                    match &data.data_files{
-                        Some(site_data) => {
-                            let compile_data = clean_data(site_data);
-                            compile_ctx = SiteContext { site: data.config.contents.clone(), page: content, loop_content: Some((*loop_content).clone()), data: Some(compile_data), baseurl: env_baseurl.clone()}
-                        },
-                        None => {
-                            compile_ctx = SiteContext { site: data.config.contents.clone(), page: content, loop_content: Some(loop_content.clone()), data: None, baseurl: env_baseurl.clone()}
-                        }
-                    }
-                    
-                },
-                None => {
-                    match &data.data_files{
-                        Some(site_data) => {
-                            let compile_data = clean_data(&site_data);
-                            compile_ctx = SiteContext { site: data.config.contents.clone(), page: content, loop_content: None, data: Some(compile_data), baseurl: env_baseurl.clone()}
-                        },
-                        None => {
-                            compile_ctx = SiteContext { site: data.config.contents.clone(), page: content, loop_content: None, data: None, baseurl: env_baseurl.clone()}
-                        }
-                    }
+            let parsed: Template = match parser.parse(&liquid_template.contents){
+                Ok(parsed) => parsed,
+                Err(e) => {
+                    failed.push(content.layout.clone());
+                    let layout_source: Vec<(PathBuf, String)> = vec![(liquid_template.path.clone(), liquid_template.contents.clone())];
+                    errors.push(liquid_error(e, &layout_source, None));
+                    continue;
                 }
-            }
-            let html: String = match process_liquid(&liquid_template.contents, &compile_ctx, &data.partials){
-                Ok(html) => html,
-                Err(e) => return Err::<(), MandyErr>(MandyErr::new(&e.to_string()))
             };
-            let _write_op: () = match coutils::write_to_file(&subdirs_items_buf.display().to_string(), &html){
-                Ok(_write_op) => _write_op,
-                Err(e) => return Err::<(), MandyErr>(MandyErr::new(&e.to_string()))
-            };            
-        }    
+            templates.insert(content.layout.clone(), parsed);
+            sources.insert(content.layout.clone(), layout_sources(&liquid_template, data));
+        }
     }
-    Ok(())
+    let compile_data: Option<HashMap<String, Value>> = data.data_files.as_ref().map(clean_data);
+    Ok((RenderInfo{ templates, sources, data: compile_data }, errors))
 }
 
-/// Compiles all SASS files the user of Mandy has in their project
-/// directory. Returns a "Result" type with an empty closure.
-pub fn compile_sass(dir: &String) -> Result<(), MandyErr>{
-    let data: SiteInfo = match gather_project_data(dir){
-        Ok(data) => data,
-        Err(e) => return Err::<(), MandyErr>(MandyErr::new(&e.to_string()))
+/// Renders the supplied content files in parallel and writes the resulting
+/// HTML files. Every content file is supplied with the path of its source. The parser, the partial templates, and the layouts are only
+/// prepared once for all content files. A content file that fails does not
+/// stop the others from being rendered and the errors of all failed content
+/// files and layouts are returned together. Content files whose layout failed
+/// are not rendered. Returns a "Result" type with an empty closure.
+pub fn compile_content_files(
+    data: &SiteInfo,
+    contents: &[(&PathBuf, &MandyContent)],
+    baseurl: &String
+) -> Result<(), MandyErr>{
+    let mut sorted: Vec<(&PathBuf, &MandyContent)> = contents.to_vec();
+    sorted.sort_by(|a, b| a.1.path.cmp(&b.1.path));
+    let to_render: Vec<&MandyContent> = sorted.iter().map(|(_source, content)| *content).collect();
+    let (render_info, mut errors): (RenderInfo, Vec<MandyErr>) = prepare_render_info(data, &to_render)?;
+    let results: Vec<Result<(), MandyErr>> = sorted
+        .par_iter()
+        .filter(|(_source, content)| render_info.templates.contains_key(&content.layout))
+        .map(|(source, content)| compile_markdown_file(source, content, data, &render_info, baseurl))
+        .collect();
+    for result in results {
+        match result {
+            Ok(_compile_op) => {},
+            Err(e) => errors.push(e)
+        };
+    }
+    if errors.is_empty(){
+        Ok(())
+    }
+    else {
+        Err::<(), MandyErr>(MandyErr::from_errors(errors))
+    }
+}
+
+/// Renders a single content file with its parsed layout and writes the resulting
+/// HTML to the path stored in the "path" field of the supplied "MandyContent"
+/// structure. The supplied path of the content file is named in any error
+/// raised while rendering it. The fields of the entry of a generated page are
+/// set on the "page" variable itself. Returns a "Result" type with an empty
+/// closure.
+pub fn compile_markdown_file(
+    source: &PathBuf,
+    content: &MandyContent,
+    data: &SiteInfo,
+    render_info: &RenderInfo,
+    baseurl: &String
+) -> Result<(), MandyErr>{
+    let html_buf: PathBuf = PathBuf::from(&content.path);
+    let path_info: PathInfo = get_path_from_buf(&html_buf)?;
+    let _create_op: () = match fs::create_dir_all(&path_info.path){
+        Ok(_create_op) => _create_op,
+        Err(e) => return Err::<(), MandyErr>(MandyErr::Io(Box::new(ErrInfo::from_source(e))).with_path(&html_buf))
     };
-    let dist_dir: &String = &data.config.contents.dist_dir;
-    let sass_files: Option<PathBuf> = match find_sass_files(dir){
-        Ok(sass_files) => sass_files,
-        Err(e) => return Err::<(), MandyErr>(MandyErr::new(&e.to_string()))
+    let template: &Template = match render_info.templates.get(&content.layout){
+        Some(template) => template,
+        None => {
+            let e: String = format!("The requested layout \"{}\" could not be found.", &content.layout);
+            return Err::<(), MandyErr>(MandyErr::Template(Box::new(ErrInfo::new(&e.to_string()))));
+        }
+    };
+    let generated_page: Option<Object> = if data.generated_pages.contains_key(source){
+        Some(get_generated_page_object(content))
+    }
+    else {
+        None
+    };
+    let compile_ctx: SiteContext = SiteContext { 
+        site: &data.config.contents, 
+        page: match &generated_page {
+            Some(page) => page,
+            None => content
+        }, 
+        loop_content: &data.loop_content, 
+        data: &render_info.data, 
+        baseurl
+    };
+    let sources: &Vec<(PathBuf, String)> = match render_info.sources.get(&content.layout){
+        Some(sources) => sources,
+        None => &Vec::new()
     };
-    match sass_files {
+    let html: String = render_template(template, &compile_ctx, sources, Some(source))?;
+    let write_op: () = match fs::write(&html_buf, &html){
+        Ok(write_op) => write_op,
+        Err(e) => return Err::<(), MandyErr>(MandyErr::Io(Box::new(ErrInfo::from_source(e))).with_path(&html_buf))
+    };
+    log_written(&html_buf, &data.staging);
+    Ok(write_op)
+}
+
+/// Compiles all SASS files the user of Mandy has in their project
+/// directory, using information already gathered on the project. 
+/// Returns a "Result" type with an empty closure.
+pub fn compile_sass(dir: &String, data: &SiteInfo) -> Result<(), MandyErr>{
+    let dist_dir: &String = &data.config.contents.dist_dir;
+    match &data.sass_dir {
         Some(sass_dir) => {
             let css: String = match grass::from_path(sass_dir.display().to_string(), &grass::Options::default()){
                 Ok(css) => css,
-                Err(e) => return Err::<(), MandyErr>(MandyErr::new(&e.to_string()))
+                Err(e) => return Err::<(), MandyErr>(MandyErr::Sass(Box::new(ErrInfo::from_source(e))).with_path(sass_dir))
             };
             let mut css_dir_buf: PathBuf = PathBuf::new();
             css_dir_buf.push(dir);
             css_dir_buf.push(dist_dir);
             css_dir_buf.push("css");
-            let _create_dir: () = match coutils::create_directory(&css_dir_buf.display().to_string()){
-                Ok(_create_dir) => _create_dir,
-                Err(e) => return Err::<(), MandyErr>(MandyErr::new(&e.to_string()))
-            };
+            if css_dir_buf.exists(){}
+            else {
+                let _create_dir: () = match fs::create_dir_all(&css_dir_buf){
+                    Ok(_create_dir) => _create_dir,
+                    Err(e) => return Err::<(), MandyErr>(MandyErr::Io(Box::new(ErrInfo::from_source(e))).with_path(&css_dir_buf))
+                };
+            }
             let mut css_file_buf: PathBuf = PathBuf::new();
             css_file_buf.push(dir);
             css_file_buf.push(dist_dir);
             css_file_buf.push("css");
             css_file_buf.push("index.css");
-            let _create_css_file: () = match coutils::create_file(&css_file_buf.display().to_string()){
-                Ok(_create_css_file) => _create_css_file,
-                Err(e) => return Err::<(), MandyErr>(MandyErr::new(&e.to_string()))
-            };
-            let write_css: () = match coutils::write_to_file(&css_file_buf.display().to_string(), &css){
+            let write_css: () = match fs::write(&css_file_buf, &css){
                 Ok(write_css) => write_css,
-                Err(e) => return Err::<(), MandyErr>(MandyErr::new(&e.to_string()))
+                Err(e) => return Err::<(), MandyErr>(MandyErr::Io(Box::new(ErrInfo::from_source(e))).with_path(&css_file_buf))
             };
-            return Ok(write_css);
+            log_written(&css_file_buf, &data.staging);
+            Ok(write_css)
         },
-        None => return Ok(())
-    };
+        None => Ok(())
+    }
 }
 
 /// Copies any static assets from the project directory to
-/// the directory containing the compiled Mandy project.
+/// the directory containing the compiled Mandy project, using information
+/// already gathered on the project. Existing files are removed before
+/// they are replaced, so files shared with the last build are left alone.
 /// Returns a "Result" type with an empty closure.
-pub fn copy_files(dir: &String) -> Result<(), MandyErr>{
-    let data: SiteInfo = match gather_project_data(dir){
-        Ok(data) => data,
-        Err(e) => return Err::<(), MandyErr>(MandyErr::new(&e.to_string()))
-    };
+pub fn copy_files(dir: &String, data: &SiteInfo) -> Result<(), MandyErr>{
     if data.config.contents.copy_files{
         let dist_dir: &String = &data.config.contents.dist_dir;
         let copy_entities: &Option<Vec<String>> = &data.config.contents.copy_entities;
@@ -337,56 +759,55 @@ pub fn copy_files(dir: &String) -> Result<(), MandyErr>{
                     if old_path_buf.exists() && old_path_buf.is_dir() {
                         new_path_buf.push(dir);
                         new_path_buf.push(dist_dir);
-                        let _dir_copy_op: () = match coutils::folder_copy(&old_path_buf.display().to_string(), &new_path_buf.display().to_string()){
-                            Ok(_dir_copy_op) => _dir_copy_op,
-                            Err(e) => return Err::<(), MandyErr>(MandyErr::new(&e.to_string()))
-                        };
+                        if let Some(dir_name) = old_path_buf.file_name() {
+                            new_path_buf.push(dir_name);
+                        }
+                        let _dir_copy_op: () = copy_directory(&old_path_buf, &new_path_buf)?;
+                        log_written(&new_path_buf, &data.staging);
                     }
                     else if old_path_buf.exists() && old_path_buf.is_file() {
                         new_path_buf.push(dir);
                         new_path_buf.push(dist_dir);
                         new_path_buf.push(entity);
-                        let _copy_op = match fs::copy(old_path_buf, new_path_buf){
+                        if new_path_buf.is_file(){
+                            let _del_op: () = match fs::remove_file(&new_path_buf){
+                                Ok(_del_op) => _del_op,
+                                Err(e) => return Err::<(), MandyErr>(MandyErr::Io(Box::new(ErrInfo::from_source(e))).with_path(&new_path_buf))
+                            };
+                        }
+                        let _copy_op = match fs::copy(&old_path_buf, &new_path_buf){
                             Ok(_copy_op) => _copy_op,
-                            Err(e) => return Err::<(), MandyErr>(MandyErr::new(&e.to_string()))
+                            Err(e) => return Err::<(), MandyErr>(MandyErr::Io(Box::new(ErrInfo::from_source(e))).with_path(&old_path_buf))
                         };
+                        log_written(&new_path_buf, &data.staging);
                     }
                     else {
                         let e: String = format!("The file at the path \"{}\" could not be found!", &old_path_buf.display().to_string());
-                        return Err::<(), MandyErr>(MandyErr::new(&e.to_string()));
+                        return Err::<(), MandyErr>(MandyErr::Config(Box::new(ErrInfo::new(&e.to_string()))).with_path(&old_path_buf));
                     }
                 }
-                return Ok(());
+                Ok(())
             },
             None => {
-                let e: String = format!("The \"copy_files\" option was set to \"true\" but no entities were supplied.");
-                return Err::<(), MandyErr>(MandyErr::new(&e.to_string()))
+                let e: String = "The \"copy_files\" option was set to \"true\" but no entities were supplied.".to_string();
+                Err::<(), MandyErr>(MandyErr::Config(Box::new(ErrInfo::new(&e.to_string()))))
             }
         }
        
     }
-    else { return Ok(()); }
+    else { Ok(()) }
     
 }
 
-/// Compiles any loop content the user may have specified.
-/// Returns a "Result" type with an empty closure.
-pub fn compile_loop_content_files(dir: &String) -> Result<(), MandyErr>{
-    let data: SiteInfo = match gather_project_data(dir){
-        Ok(data) => data,
-        Err(e) => return Err::<(), MandyErr>(MandyErr::new(&e.to_string()))
-    };
-    let loop_content = match find_loop_content_files(dir, &data.config.contents){
-        Ok(loop_content) => loop_content,
-        Err(e) => return Err::<(), MandyErr>(MandyErr::new(&e.to_string()))
-    };
-    let alc: HashMap<String,Vec<MandyContent>> = match loop_content{
+/// Compiles any loop content the user may have specified, using information
+/// already gathered on the project. Returns a "Result" type with an empty closure.
+pub fn compile_loop_content_files(data: &SiteInfo) -> Result<(), MandyErr>{
+    let alc: &HashMap<String,Vec<MandyContent>> = match &data.loop_content{
         Some(alc) => alc,
         None => return Ok(())
     };
     let mut file_vec: Vec<String> = Vec::new();
-    for (_key, value) in alc.iter().enumerate(){
-        let (_direct,content) = value;
+    for (_direct, content) in alc.iter(){
         for path in content {
             file_vec.push(path.path.clone());
         }
@@ -398,8 +819,189 @@ pub fn compile_loop_content_files(dir: &String) -> Result<(), MandyErr>{
         if new_path_buf.exists(){}
         else {
             let e: String = format!("The following file from the loop content directories could not be generated: \"{}\"", file_clone);
-            return Err::<(), MandyErr>(MandyErr::new(&e.to_string()));
+            return Err::<(), MandyErr>(MandyErr::Io(Box::new(ErrInfo::new(&e.to_string()))).with_path(&new_path_buf));
         }
     }
     Ok(())
+}
+
+/// Tests for building into
+/// a staging directory.
+#[cfg(test)]
+mod tests {
+
+    /// Importing the standard
+    /// library for working with
+    /// the filesystem.
+    use std::fs;
+
+    /// Importing the "PathBuf"
+    /// structure from Rust's standard
+    /// library.
+    use std::path::PathBuf;
+
+    /// Importing the structure holding
+    /// a directory created for a test.
+    use crate::modules::testing::TempDir;
+
+    /// Importing the function to carry
+    /// files over from the last build.
+    use super::reuse_output;
+
+    /// Importing the function to
+    /// compile a Mandy project.
+    use super::compile_project_with_baseurl;
+
+    /// Importing Mandy's error-handling
+    /// structure to handle errors.
+    use crate::modules::err::MandyErr;
+
+    /// Importing the enum to
+    /// describe the format of the
+    /// configuration file.
+    use crate::modules::units::UserDataFileType;
+
+    /// Importing the function
+    /// to create a new project.
+    use crate::modules::scaffold::init_project;
+
+    /// Importing the configuration
+    /// file of a new project in YAML
+    /// format.
+    use crate::modules::scaffold::STARTER_YML_CONFIG;
+
+    /// Importing the name of the
+    /// file the build cache is
+    /// stored in.
+    use crate::modules::cache::CACHE_FILE;
+
+    /// Importing the "ThreadPoolBuilder"
+    /// structure to render pages with
+    /// a set number of threads.
+    use rayon::ThreadPoolBuilder;
+
+    /// Importing the "ThreadPool"
+    /// structure to render pages with
+    /// a set number of threads.
+    use rayon::ThreadPool;
+
+    /// Importing the "WalkDir"
+    /// structure to read every
+    /// compiled file.
+    use walkdir::WalkDir;
+
+    /// Importing the "BTreeMap"
+    /// structure to compare compiled
+    /// projects in a fixed order.
+    use std::collections::BTreeMap;
+
+    /// Compiles the Mandy project in the supplied directory from
+    /// scratch with the supplied number of threads and returns the
+    /// contents of every compiled file by its path.
+    fn compile_with_jobs(project: &TempDir, jobs: usize) -> BTreeMap<PathBuf, Vec<u8>> {
+        let _del_op = fs::remove_file(project.path.join(CACHE_FILE));
+        let pool: ThreadPool = ThreadPoolBuilder::new().num_threads(jobs).build().unwrap();
+        pool.install(|| compile_project_with_baseurl(&project.dir(), &String::new())).unwrap();
+        let dist_buf: PathBuf = project.path.join("dist");
+        let mut result: BTreeMap<PathBuf, Vec<u8>> = BTreeMap::new();
+        for entry in WalkDir::new(&dist_buf).into_iter().map(|entry| entry.unwrap()) {
+            if entry.file_type().is_file() {
+                let relative: PathBuf = entry.path().strip_prefix(&dist_buf).unwrap().to_path_buf();
+                result.insert(relative, fs::read(entry.path()).unwrap());
+            }
+        }
+        result
+    }
+
+    #[test]
+    fn carries_files_over_from_the_last_build() {
+        let project: TempDir = TempDir::new("compile-reuse");
+        let dist_buf: PathBuf = project.path.join("dist");
+        let staging_buf: PathBuf = project.path.join(".dist-staging");
+        project.write("dist/page/index.html", "page");
+        let staged_buf: PathBuf = staging_buf.join("page").join("index.html");
+        assert!(reuse_output(&staged_buf, &staging_buf, &dist_buf).unwrap());
+        assert_eq!(fs::read_to_string(&staged_buf).unwrap(), "page");
+        let missing_buf: PathBuf = staging_buf.join("missing.html");
+        assert!(!reuse_output(&missing_buf, &staging_buf, &dist_buf).unwrap());
+        assert!(!missing_buf.exists());
+    }
+
+    #[test]
+    fn names_a_missing_project_directory() {
+        let dir: String = String::from("/nonexistent/mandy-project");
+        match compile_project_with_baseurl(&dir, &String::new()) {
+            Err(MandyErr::Io(info)) => assert_eq!(info.path, Some(PathBuf::from(&dir))),
+            other => panic!("A missing project should be an IO error, not {:?}.", other)
+        };
+    }
+
+    #[test]
+    fn reports_every_build_error_together() {
+        let project: TempDir = TempDir::new("compile-errors");
+        project.write("config.yml", "title: Site\ndist_dir: dist\n");
+        project.write("layouts/default.liquid", "{{ page.content }}");
+        project.write("partials/head.liquid", "");
+        project.write("index.markdown", "---\nlayout: missing\n---\n# Hi");
+        project.write("sass/index.scss", "body { color: ; ");
+        let error: MandyErr = match compile_project_with_baseurl(&project.dir(), &String::new()) {
+            Err(error) => error,
+            Ok(_compile_op) => panic!("A project with two errors should not compile.")
+        };
+        let errors: Vec<&MandyErr> = error.errors();
+        assert!(errors.iter().any(|e| e.info().details.contains("missing")), "{:?}", errors);
+        assert!(errors.iter().any(|e| matches!(e, MandyErr::Sass(_))), "{:?}", errors);
+        assert!(!project.path.join("dist").exists());
+        assert!(!project.path.join(".dist-staging").exists());
+    }
+
+    #[test]
+    fn rebuilds_in_place_and_removes_stale_output() {
+        let project: TempDir = TempDir::new("compile-stale");
+        init_project(&project.dir(), &UserDataFileType::YAML).unwrap();
+        let config: String = STARTER_YML_CONFIG.replace("copy_files: false\ncopy_entities: []", "copy_files: true\ncopy_entities:\n  - static\n  - notes.txt");
+        project.write("config.yml", &config);
+        project.write("static/logo.txt", "logo");
+        project.write("notes.txt", "notes");
+        project.write("posts/second.markdown", "---\nlayout: default\ntitle: Second\n---\nSecond post.");
+        compile_project_with_baseurl(&project.dir(), &String::new()).unwrap();
+        let dist_buf: PathBuf = project.path.join("dist");
+        assert!(dist_buf.join("posts/second/index.html").exists());
+        fs::remove_file(project.path.join("posts/second.markdown")).unwrap();
+        project.write("dist/stray.html", "stray");
+        compile_project_with_baseurl(&project.dir(), &String::new()).unwrap();
+        assert!(dist_buf.join("index.html").exists());
+        assert!(dist_buf.join("posts/hello-world/index.html").exists());
+        assert!(!dist_buf.join("posts/second").exists());
+        assert!(!dist_buf.join("stray.html").exists());
+        assert_eq!(fs::read_to_string(dist_buf.join("static/logo.txt")).unwrap(), "logo");
+        assert_eq!(fs::read_to_string(dist_buf.join("notes.txt")).unwrap(), "notes");
+    }
+
+    #[test]
+    fn renders_in_parallel_like_a_serial_build() {
+        let project: TempDir = TempDir::new("compile-parallel");
+        init_project(&project.dir(), &UserDataFileType::YAML).unwrap();
+        for idx in 0..24 {
+            let post: String = format!("---\nlayout: default\ntitle: Post {}\n---\nThis is post number {}.", idx, idx);
+            project.write(&format!("posts/post-{}.markdown", idx), &post);
+        }
+        let serial: BTreeMap<PathBuf, Vec<u8>> = compile_with_jobs(&project, 1);
+        let parallel: BTreeMap<PathBuf, Vec<u8>> = compile_with_jobs(&project, 4);
+        assert!(serial.contains_key(&PathBuf::from("posts/post-23/index.html")));
+        assert_eq!(serial.keys().collect::<Vec<&PathBuf>>(), parallel.keys().collect::<Vec<&PathBuf>>());
+        assert!(serial == parallel, "The parallel build differs from the serial one.");
+    }
+
+    #[test]
+    fn fails_builds_with_the_exit_code_for_the_error() {
+        let project: TempDir = TempDir::new("compile-exit-codes");
+        init_project(&project.dir(), &UserDataFileType::YAML).unwrap();
+        project.write("posts/broken.markdown", "---\ntitle: [unclosed\n---\nBroken.");
+        let content_error: MandyErr = compile_project_with_baseurl(&project.dir(), &String::new()).unwrap_err();
+        assert_eq!(content_error.exit_code(), 4, "{:?}", content_error);
+        project.write("config.yml", "title: [unclosed");
+        let config_error: MandyErr = compile_project_with_baseurl(&project.dir(), &String::new()).unwrap_err();
+        assert_eq!(config_error.exit_code(), 3, "{:?}", config_error);
+    }
 }
\ No newline at end of file
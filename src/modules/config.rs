@@ -0,0 +1,553 @@
+/*
+Mandy by Alyx Shang.
+Licensed under the FSL v1.
+*/
+
+/// Importing the "PathBuf"
+/// structure from Rust's standard
+/// library.
+use std::path::PathBuf;
+
+/// Importing the "Path"
+/// structure to look at the
+/// components of a path.
+use std::path::Path;
+
+/// Importing the "Component"
+/// enum to look at the
+/// components of a path.
+use std::path::Component;
+
+/// Importing the "Value"
+/// enum from the "serde_json"
+/// crate to look at the keys
+/// of configuration files.
+use serde_json::Value;
+
+/// Importing Mandy's error-handling
+/// structure to handle errors.
+use super::err::MandyErr;
+
+/// Importing the structure that
+/// holds what is known about
+/// an error.
+use super::err::ErrInfo;
+
+/// Importing the structure
+/// holding the options of a
+/// Mandy project.
+use super::units::MandyConfig;
+
+/// Importing the placeholder
+/// for slugs in the URLs of
+/// page generators.
+use super::generators::SLUG_PLACEHOLDER;
+
+/// The options a configuration
+/// file of a Mandy project
+/// can set.
+pub const CONFIG_KEYS: [&str; 14] = [
+    "title",
+    "description",
+    "tl_domain",
+    "prod_url",
+    "dev_url",
+    "dist_dir",
+    "seo",
+    "copy_files",
+    "copy_entities",
+    "has_loop_content",
+    "loop_content_dirs",
+    "user_config",
+    "generators",
+    "markdown"
+];
+
+/// The options the "markdown"
+/// section of a configuration
+/// file can set.
+pub const MARKDOWN_KEYS: [&str; 8] = [
+    "gfm",
+    "tables",
+    "strikethrough",
+    "task_lists",
+    "autolinks",
+    "footnotes",
+    "raw_html",
+    "math"
+];
+
+/// The directories of a Mandy
+/// project that hold its sources
+/// and what they hold.
+pub const SOURCE_DIRS: [(&str, &str); 5] = [
+    ("layouts", "the layouts"),
+    ("partials", "the partial templates"),
+    ("data", "the data files"),
+    ("sass", "the SASS files"),
+    ("archetypes", "the archetypes")
+];
+
+/// Returns the directory compiled
+/// projects are written to if the
+/// "dist_dir" option is not set.
+pub fn default_dist_dir() -> String {
+    String::from("dist")
+}
+
+/// Fills in options of the supplied configuration that were not set in the
+/// supplied parsed configuration file. Trailing slashes are removed from
+/// URLs, as the URLs of pages start with one, and "." components are removed
+/// from the "dist_dir" option. The "tl_domain" option defaults
+/// to the "prod_url" option if that is a full URL. The "has_loop_content" and
+/// "copy_files" options default to whether the lists they switch on are set.
+pub fn apply_config_defaults(config: &mut MandyConfig, value: &Value){
+    let is_set = |key: &str| -> bool {
+        match value.get(key){
+            Some(option) => !option.is_null(),
+            None => false
+        }
+    };
+    for url in [&mut config.tl_domain, &mut config.prod_url, &mut config.dev_url] {
+        let trimmed: String = url.trim_end_matches('/').to_string();
+        *url = trimmed;
+    }
+    let dist_buf: PathBuf = normalize_project_path(&config.dist_dir);
+    if dist_buf.components().next().is_some(){
+        config.dist_dir = dist_buf.display().to_string();
+    }
+    if config.tl_domain.is_empty() && is_full_url(&config.prod_url){
+        config.tl_domain = config.prod_url.clone();
+    }
+    if !is_set("has_loop_content"){
+        config.has_loop_content = match &config.loop_content_dirs {
+            Some(dirs) => !dirs.is_empty(),
+            None => false
+        };
+    }
+    if !is_set("copy_files"){
+        config.copy_files = match &config.copy_entities {
+            Some(entities) => !entities.is_empty(),
+            None => false
+        };
+    }
+}
+
+/// Checks whether the supplied URL
+/// starts with "http://" or "https://"
+/// followed by a host.
+pub fn is_full_url(url: &str) -> bool {
+    let rest: &str = match url.strip_prefix("https://").or(url.strip_prefix("http://")){
+        Some(rest) => rest,
+        None => return false
+    };
+    match rest.split('/').next(){
+        Some(host) => !host.is_empty(),
+        None => false
+    }
+}
+
+/// Checks the supplied URL from the option with the supplied name. Empty
+/// URLs are allowed. Otherwise the URL must be a full URL or, if paths
+/// are allowed, a path starting with a slash. The URL must not contain
+/// whitespace. Returns a description of the problem if there is one.
+pub fn check_url(key: &str, url: &String, allow_path: &bool) -> Option<String> {
+    if url.is_empty(){
+        None
+    }
+    else if url.chars().any(|c| c.is_whitespace()){
+        Some(format!("The \"{}\" option \"{}\" must not contain whitespace.", key, url))
+    }
+    else if !is_full_url(url) && (!*allow_path || !url.starts_with('/')){
+        if *allow_path {
+            Some(format!("The \"{}\" option \"{}\" must be a full URL starting with \"http://\" or \"https://\", or a path starting with \"/\".", key, url))
+        }
+        else {
+            Some(format!("The \"{}\" option \"{}\" must be a full URL starting with \"http://\" or \"https://\".", key, url))
+        }
+    }
+    else {
+        None
+    }
+}
+
+/// Checks the supplied path from the option with the supplied name. The path
+/// must not be empty, must be relative to the project, and must stay inside
+/// it. Returns a description of the problem if there is one.
+pub fn check_project_path(key: &str, path: &String) -> Option<String> {
+    if path.trim().is_empty(){
+        return Some(format!("The \"{}\" option must not contain an empty path.", key));
+    }
+    let mut depth: usize = 0;
+    for component in Path::new(path).components() {
+        match component {
+            Component::Normal(_name) => depth += 1,
+            Component::CurDir => {},
+            Component::ParentDir => {
+                return Some(format!("The path \"{}\" in the \"{}\" option must not contain \"..\".", path, key));
+            },
+            Component::RootDir | Component::Prefix(_) => {
+                return Some(format!("The path \"{}\" in the \"{}\" option must be relative to the project.", path, key));
+            }
+        };
+    }
+    if depth == 0 {
+        return Some(format!("The path \"{}\" in the \"{}\" option must name a file or directory inside the project.", path, key));
+    }
+    None
+}
+
+/// Returns the supplied path from an option without any "." components, so
+/// that "./dist" and "dist/" are compared as the same path as "dist".
+pub fn normalize_project_path(path: &String) -> PathBuf {
+    Path::new(path)
+        .components()
+        .filter(|component| component != &Component::CurDir)
+        .collect()
+}
+
+/// Checks the directory compiled projects are written to. It must be a
+/// path inside the project that is not one of the directories holding the
+/// sources of the project, as it is replaced on every build. Returns a
+/// description of the problem if there is one.
+pub fn check_dist_dir(config: &MandyConfig) -> Option<String> {
+    let dist_dir: &String = &config.dist_dir;
+    if let Some(problem) = check_project_path("dist_dir", dist_dir) {
+        return Some(problem);
+    }
+    let dist_buf: PathBuf = normalize_project_path(dist_dir);
+    for (name, holds) in SOURCE_DIRS {
+        if dist_buf == Path::new(name){
+            return Some(format!(
+                "The \"dist_dir\" option cannot be \"{}\", as this directory holds {} of the project.",
+                dist_dir,
+                holds
+            ));
+        }
+    }
+    if let Some(dirs) = &config.loop_content_dirs {
+        for dir in dirs {
+            if dist_buf == normalize_project_path(dir){
+                return Some(format!(
+                    "The \"dist_dir\" option cannot be \"{}\", as this directory is in the \"loop_content_dirs\" option.",
+                    dist_dir
+                ));
+            }
+        }
+    }
+    None
+}
+
+/// Returns the line and the column of the supplied option in the supplied
+/// source of a configuration file in any format, if the option is set
+/// at the top of a line. Tables in TOML files, such as "[[generators]]",
+/// are found by their headers.
+pub fn find_config_key(src: &str, key: &str) -> Option<(usize, usize)> {
+    for (idx, line) in src.lines().enumerate() {
+        let trimmed: &str = line.trim_start();
+        let header: &str = trimmed.trim_start_matches('[').trim_end().trim_end_matches(']');
+        if trimmed.starts_with('[') && header.trim() == key {
+            return Some((idx + 1, line.len() - trimmed.len() + 1));
+        }
+        let unquoted: &str = trimmed.trim_start_matches(['"', '\'']);
+        let rest: &str = match unquoted.strip_prefix(key){
+            Some(rest) => rest,
+            None => continue
+        };
+        let rest: &str = rest.trim_start_matches(['"', '\'']).trim_start();
+        if rest.starts_with(':') || rest.starts_with('='){
+            return Some((idx + 1, line.len() - trimmed.len() + 1));
+        }
+    }
+    None
+}
+
+/// Turns the supplied description of a problem with the supplied option into
+/// an error pointing to the file that sets the option. The supplied sources
+/// are searched in reverse order, so that an overlay file is found before the
+/// configuration file it is applied to.
+fn config_error(problem: &str, key: &str, sources: &[(PathBuf, String)]) -> MandyErr {
+    let error: MandyErr = MandyErr::Config(Box::new(ErrInfo::new(problem)));
+    for (path, src) in sources.iter().rev() {
+        if let Some((line, column)) = find_config_key(src, key) {
+            return error
+                .with_path(path)
+                .with_location(&line, &column)
+                .with_snippet(src);
+        }
+    }
+    match sources.first(){
+        Some((path, _src)) => error.with_path(path),
+        None => error
+    }
+}
+
+/// Finds the option of an overlay file that keeps the supplied merged configuration
+/// from being read. Every option is put back to its value in the supplied configuration
+/// before the merge, one at a time, until the configuration can be read. Returns
+/// "None" if no single option is at fault.
+pub fn find_overlaid_key(base: &Value, merged: &Value) -> Option<String> {
+    let (base_map, merged_map) = match (base, merged) {
+        (Value::Object(base_map), Value::Object(merged_map)) => (base_map, merged_map),
+        _ => return None
+    };
+    for (key, value) in merged_map {
+        if base_map.get(key) == Some(value) {
+            continue;
+        }
+        let mut restored: serde_json::Map<String, Value> = merged_map.clone();
+        match base_map.get(key){
+            Some(base_value) => {
+                restored.insert(key.clone(), base_value.clone());
+            },
+            None => {
+                restored.remove(key);
+            }
+        };
+        if serde_json::from_value::<MandyConfig>(Value::Object(restored)).is_ok(){
+            return Some(key.clone());
+        }
+    }
+    None
+}
+
+/// Turns the supplied error from reading a configuration after its overlay file
+/// has been applied into an error pointing to the option of the overlay file at
+/// fault. The supplied sources are the paths and the code of the configuration
+/// file and its overlay file. If no single option is at fault, the error points
+/// to the overlay file.
+pub fn overlay_error(error: serde_json::Error, base: &Value, merged: &Value, sources: &[(PathBuf, String)]) -> MandyErr {
+    let overlay: Option<&(PathBuf, String)> = sources.last();
+    let error: MandyErr = MandyErr::Config(Box::new(ErrInfo::from_source(error)));
+    if let Some(key) = find_overlaid_key(base, merged) {
+        for (path, src) in sources.iter().rev() {
+            if let Some((line, column)) = find_config_key(src, &key) {
+                return error
+                    .with_path(path)
+                    .with_location(&line, &column)
+                    .with_snippet(src);
+            }
+        }
+    }
+    match overlay {
+        Some((path, _src)) => {
+            let note: String = format!("after applying the overlay file \"{}\"", path.display());
+            error.with_path(path).with_note(&note)
+        },
+        None => error
+    }
+}
+
+/// Checks the options of the supplied configuration after defaults have been
+/// applied. The supplied sources are the paths and the code of the configuration
+/// file and its overlay file and are used to point to the option causing a problem.
+/// All problems are returned together. Returns a "Result" type with an empty closure.
+pub fn validate_config(config: &MandyConfig, sources: &[(PathBuf, String)]) -> Result<(), MandyErr>{
+    let mut errors: Vec<MandyErr> = Vec::new();
+    if config.title.trim().is_empty(){
+        let problem: String = String::from("The \"title\" option must be set to the title of the site.");
+        errors.push(config_error(&problem, "title", sources));
+    }
+    let urls: Vec<(&str, &String, bool)> = vec![
+        ("tl_domain", &config.tl_domain, false),
+        ("prod_url", &config.prod_url, true),
+        ("dev_url", &config.dev_url, true)
+    ];
+    for (key, url, allow_path) in urls {
+        if let Some(problem) = check_url(key, url, &allow_path) {
+            errors.push(config_error(&problem, key, sources));
+        }
+    }
+    if config.seo && config.tl_domain.is_empty(){
+        let problem: String = String::from(
+            "The \"seo\" option needs the \"tl_domain\" option or a full \"prod_url\" option to build the sitemap."
+        );
+        errors.push(config_error(&problem, "seo", sources));
+    }
+    if let Some(problem) = check_dist_dir(config) {
+        errors.push(config_error(&problem, "dist_dir", sources));
+    }
+    let lists: Vec<(&str, &Option<Vec<String>>)> = vec![
+        ("copy_entities", &config.copy_entities),
+        ("loop_content_dirs", &config.loop_content_dirs)
+    ];
+    for (key, list) in lists {
+        if let Some(paths) = list {
+            for path in paths {
+                if let Some(problem) = check_project_path(key, path) {
+                    errors.push(config_error(&problem, key, sources));
+                }
+            }
+        }
+    }
+    if let Some(generators) = &config.generators {
+        for generator in generators {
+            if let Some(problem) = check_generator_url(&generator.url) {
+                errors.push(config_error(&problem, "generators", sources));
+            }
+            if generator.data.trim().is_empty() || generator.layout.trim().is_empty() || generator.slug_field.trim().is_empty(){
+                let problem: String = format!(
+                    "The page generator with the URL \"{}\" must name a data file, a layout, and a slug field.",
+                    &generator.url
+                );
+                errors.push(config_error(&problem, "generators", sources));
+            }
+        }
+    }
+    if errors.is_empty(){
+        Ok(())
+    }
+    else {
+        Err::<(), MandyErr>(MandyErr::from_errors(errors))
+    }
+}
+
+/// Checks the URL pattern of a page generator. It must start with "/" and
+/// contain ":slug", so that every entry gets its own page. Returns a description
+/// of the problem if there is one.
+pub fn check_generator_url(url: &String) -> Option<String> {
+    if !url.starts_with('/'){
+        Some(format!("The URL \"{}\" of a page generator must start with \"/\".", url))
+    }
+    else if !url.contains(SLUG_PLACEHOLDER){
+        Some(format!("The URL \"{}\" of a page generator must contain \"{}\", so that every entry gets its own page.", url, SLUG_PLACEHOLDER))
+    }
+    else if url.split('/').any(|segment| segment == ".."){
+        Some(format!("The URL \"{}\" of a page generator must not contain \"..\".", url))
+    }
+    else {
+        None
+    }
+}
+
+/// Returns the number of single-character edits needed
+/// to turn one of the supplied strings into the other.
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let b_chars: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b_chars.len()).collect();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current: Vec<usize> = vec![i + 1];
+        for (j, b_char) in b_chars.iter().enumerate() {
+            let cost: usize = if &a_char == b_char { 0 } else { 1 };
+            current.push((previous[j] + cost).min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b_chars.len()]
+}
+
+/// Finds the keys in the supplied map that are not among the supplied known
+/// options. Every unknown key is returned as a warning pointing to the supplied
+/// path and suggesting the closest option if the key looks like a typo of it.
+/// The supplied section is put in front of the key in the message, if any.
+fn find_unknown_keys(
+    path: &Path,
+    src: &str,
+    value: &Value,
+    known: &[&str],
+    section: Option<&str>
+) -> Vec<ErrInfo> {
+    let mut warnings: Vec<ErrInfo> = Vec::new();
+    let map = match value.as_object(){
+        Some(map) => map,
+        None => return warnings
+    };
+    for key in map.keys() {
+        if known.contains(&key.as_str()){
+            continue;
+        }
+        let name: String = match section {
+            Some(section) => format!("{}.{}", section, key),
+            None => key.to_string()
+        };
+        let message: String = format!("The option \"{}\" is not known and is ignored.", name);
+        let mut warning: ErrInfo = ErrInfo::new(&message);
+        warning.path = Some(path.to_path_buf());
+        if let Some((line, column)) = find_config_key(src, key) {
+            warning.line = Some(line);
+            warning.column = Some(column);
+        }
+        let closest: Option<(usize, &str)> = known
+            .iter()
+            .map(|option| (edit_distance(key, option), *option))
+            .min();
+        match closest {
+            Some((distance, option)) if distance <= 3 => {
+                warning.notes.push(format!("did you mean \"{}\"?", option));
+            },
+            _ => {}
+        };
+        warnings.push(warning);
+    }
+    warnings
+}
+
+/// Finds the keys in the supplied parsed configuration file that are not
+/// options of a Mandy project, at the top level and in the "markdown"
+/// section. Every unknown key is returned as a warning pointing to the
+/// supplied path.
+pub fn find_unknown_config_keys(path: &Path, src: &str, value: &Value) -> Vec<ErrInfo> {
+    let mut warnings: Vec<ErrInfo> = find_unknown_keys(path, src, value, &CONFIG_KEYS, None);
+    if let Some(markdown) = value.get("markdown") {
+        warnings.append(&mut find_unknown_keys(path, src, markdown, &MARKDOWN_KEYS, Some("markdown")));
+    }
+    warnings
+}
+
+/// Tests for checking
+/// configuration options.
+#[cfg(test)]
+mod tests {
+
+    /// Importing the "json"
+    /// macro to build configuration
+    /// values.
+    use serde_json::json;
+
+    /// Importing the "Value"
+    /// enum from the "serde_json"
+    /// crate.
+    use serde_json::Value;
+
+    /// Importing the function
+    /// to check URLs.
+    use super::check_url;
+
+    /// Importing the function
+    /// to fill in the defaults
+    /// of a configuration.
+    use super::apply_config_defaults;
+
+    /// Importing the structure
+    /// holding the options of a
+    /// Mandy project.
+    use super::MandyConfig;
+
+    #[test]
+    fn checks_urls(){
+        assert!(check_url("prod_url", &String::new(), &false).is_none());
+        assert!(check_url("prod_url", &"https://example.com".to_string(), &false).is_none());
+        assert!(check_url("prod_url", &"http://example.com/blog".to_string(), &false).is_none());
+        assert!(check_url("dev_url", &"/blog".to_string(), &true).is_none());
+        assert!(check_url("prod_url", &"/blog".to_string(), &false).is_some());
+        assert!(check_url("prod_url", &"example.com".to_string(), &true).is_some());
+        assert!(check_url("prod_url", &"https://".to_string(), &false).is_some());
+        assert!(check_url("prod_url", &"https://exa mple.com".to_string(), &false).is_some());
+    }
+
+    #[test]
+    fn normalizes_urls_and_the_output_dir(){
+        let value: Value = json!({
+            "prod_url": "https://example.com/",
+            "dev_url": "http://localhost:8080//",
+            "dist_dir": "./dist/"
+        });
+        let mut config: MandyConfig = serde_json::from_value(value.clone()).unwrap();
+        apply_config_defaults(&mut config, &value);
+        assert_eq!(config.prod_url, "https://example.com");
+        assert_eq!(config.dev_url, "http://localhost:8080");
+        assert_eq!(config.tl_domain, "https://example.com");
+        assert_eq!(config.dist_dir, "dist");
+        assert!(check_url("prod_url", &config.prod_url, &false).is_none());
+    }
+}
\ No newline at end of file
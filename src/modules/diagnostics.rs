@@ -0,0 +1,282 @@
+/*
+Mandy by Alyx Shang.
+Licensed under the FSL v1.
+*/
+
+/// Importing the "PathBuf"
+/// structure from Rust's standard
+/// library.
+use std::path::PathBuf;
+
+/// Importing Mandy's error-handling
+/// structure to handle errors.
+use super::err::MandyErr;
+
+/// Importing the structure that
+/// holds what is known about
+/// an error.
+use super::err::ErrInfo;
+
+/// Importing the function to
+/// render a snippet of source
+/// code with a caret.
+use super::err::render_snippet;
+
+/// Importing the "SiteInfo"
+/// structure to read information
+/// gathered on a Mandy project.
+use super::units::SiteInfo;
+
+/// Importing the "LayoutFile"
+/// structure to read the path
+/// and the code of a layout.
+use super::units::LayoutFile;
+
+/// A structure to hold the
+/// parts of an error message
+/// from the "liquid" crate.
+#[derive(Debug, Clone)]
+pub struct LiquidReport {
+    pub message: String,
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+    pub line_text: Option<String>,
+    pub traces: Vec<String>,
+    pub context: Vec<(String, String)>
+}
+
+/// Splits the message of an error from the "liquid" crate into its parts:
+/// the message itself, the line and the column for errors found while parsing,
+/// the calls that led to the error, and the values supplied as context. Only
+/// the values supplied for the error itself are kept, not those supplied for
+/// the calls that led to it.
+pub fn parse_liquid_message(message: &str) -> LiquidReport {
+    let mut report: LiquidReport = LiquidReport {
+        message: String::new(),
+        line: None,
+        column: None,
+        line_text: None,
+        traces: Vec::new(),
+        context: Vec::new()
+    };
+    let body: &str = message.strip_prefix("liquid: ").unwrap_or(message);
+    let mut lines = body.lines();
+    let first: &str = lines.next().unwrap_or("").trim();
+    let is_parse_error: bool = first.starts_with("-->");
+    if is_parse_error {
+        let position: &str = first.trim_start_matches("-->").trim();
+        let mut parts = position.split(':');
+        report.line = parts.next().and_then(|line| line.trim().parse::<usize>().ok());
+        report.column = parts.next().and_then(|column| column.trim().parse::<usize>().ok());
+    }
+    else {
+        report.message = first.to_string();
+    }
+    for line in lines {
+        let trimmed: &str = line.trim();
+        if is_parse_error {
+            if let Some(text) = trimmed.strip_prefix("= ") {
+                report.message = text.to_string();
+            }
+            else if let Some((number, text)) = line.split_once(" | ") {
+                if number.trim().parse::<usize>().ok() == report.line {
+                    report.line_text = Some(text.to_string());
+                }
+            }
+        }
+        else if let Some(trace) = trimmed.strip_prefix("from: ") {
+            report.traces.push(trace.to_string());
+        }
+        else if !report.traces.is_empty() {}
+        else if let Some((key, value)) = trimmed.split_once('=') {
+            report.context.push((key.to_string(), value.to_string()));
+        }
+    }
+    if report.message.is_empty() {
+        report.message = first.to_string();
+    }
+    report
+}
+
+/// Finds the first occurrence of the supplied text in the supplied source code.
+/// Returns the line and the column of the occurrence, both starting at one.
+pub fn find_in_source(source: &str, needle: &str) -> Option<(usize, usize)> {
+    if needle.trim().is_empty() {
+        return None;
+    }
+    for (idx, line) in source.lines().enumerate() {
+        if let Some(byte_idx) = line.find(needle) {
+            return Some((idx + 1, line[..byte_idx].chars().count() + 1));
+        }
+    }
+    None
+}
+
+/// Returns the paths and the code of every partial template in a Mandy
+/// project. The partial templates are sorted by their name.
+pub fn partial_sources(data: &SiteInfo) -> Vec<(PathBuf, String)> {
+    let project_buf: PathBuf = match data.config.path.parent() {
+        Some(parent) => parent.to_path_buf(),
+        None => PathBuf::new()
+    };
+    let mut names: Vec<&String> = data.partials.keys().collect();
+    names.sort();
+    let mut result: Vec<(PathBuf, String)> = Vec::new();
+    for name in names {
+        let mut partial_buf: PathBuf = project_buf.clone();
+        partial_buf.push("partials");
+        partial_buf.push(format!("{}.liquid", name));
+        result.push((partial_buf, data.partials[name].clone()));
+    }
+    result
+}
+
+/// Returns the path and the code of the supplied layout followed by the
+/// paths and the code of every partial template in a Mandy project.
+pub fn layout_sources(layout: &LayoutFile, data: &SiteInfo) -> Vec<(PathBuf, String)> {
+    let mut result: Vec<(PathBuf, String)> = vec![(layout.path.clone(), layout.contents.clone())];
+    result.append(&mut partial_sources(data));
+    result
+}
+
+/// Turns an error from the "liquid" crate into a diagnostic. The supplied
+/// sources are searched for the code that failed, in order, so the first
+/// source should be the layout being parsed or rendered. The diagnostic names
+/// the file the failing code is in, the line and the column of the code, and
+/// shows a snippet of the code with a caret under it. If a content file was
+/// being rendered, its path is added as a note.
+pub fn liquid_error(
+    e: liquid::Error,
+    sources: &[(PathBuf, String)],
+    rendering: Option<&PathBuf>
+) -> MandyErr {
+    let report: LiquidReport = parse_liquid_message(&e.to_string());
+    let mut info: ErrInfo = ErrInfo::from_source(e);
+    info.details = report.message.clone();
+    let mut location: Option<(usize, usize, usize, usize)> = None;
+    match (&report.line, &report.column) {
+        (Some(line), Some(column)) => {
+            let mut source_idx: usize = 0;
+            if let Some(line_text) = &report.line_text {
+                for (idx, (_path, source)) in sources.iter().enumerate() {
+                    if source.lines().nth(line - 1) == Some(line_text.as_str()) {
+                        source_idx = idx;
+                        break;
+                    }
+                }
+            }
+            location = Some((source_idx, *line, *column, 1));
+        },
+        _ => {
+            let mut needles: Vec<String> = Vec::new();
+            let variable: Option<&String> = report.context
+                .iter()
+                .find(|(key, _value)| key == "variable")
+                .map(|(_key, value)| value);
+            for (key, value) in &report.context {
+                if key.starts_with("requested") {
+                    if let Some(variable) = variable {
+                        needles.push(format!("{}.{}", variable, value));
+                        needles.push(format!("{}[\"{}\"]", variable, value));
+                    }
+                    needles.push(value.clone());
+                }
+            }
+            needles.append(&mut report.traces.clone());
+            'search: for needle in &needles {
+                for (idx, (_path, source)) in sources.iter().enumerate() {
+                    if let Some((line, column)) = find_in_source(source, needle) {
+                        location = Some((idx, line, column, needle.chars().count()));
+                        break 'search;
+                    }
+                }
+            }
+        }
+    };
+    match location {
+        Some((source_idx, line, column, width)) => {
+            if let Some((path, source)) = sources.get(source_idx) {
+                if !path.as_os_str().is_empty() {
+                    info.path = Some(path.clone());
+                }
+                info.line = Some(line);
+                info.column = Some(column);
+                info.snippet = render_snippet(source, &line, &column, &width);
+            }
+        },
+        None => {
+            match sources.first() {
+                Some((path, _source)) if !path.as_os_str().is_empty() => info.path = Some(path.clone()),
+                _ => {}
+            };
+        }
+    };
+    for (key, value) in &report.context {
+        if !key.starts_with("available") {
+            info.notes.push(format!("{}: {}", key, value));
+        }
+    }
+    if let Some(content_path) = rendering {
+        info.notes.push(format!("while rendering \"{}\"", content_path.display()));
+    }
+    MandyErr::Template(Box::new(info))
+}
+
+
+/// Tests for reading the
+/// messages of Liquid errors.
+#[cfg(test)]
+mod tests {
+
+    /// Importing the "ParserBuilder"
+    /// structure from the "liquid"
+    /// crate to produce real errors.
+    use liquid::ParserBuilder;
+
+    /// Importing the "object"
+    /// macro from the "liquid"
+    /// crate to supply context.
+    use liquid::object;
+
+    /// Importing the function to
+    /// read the messages of Liquid
+    /// errors.
+    use super::parse_liquid_message;
+
+    /// Importing the structure holding
+    /// the parts of the message of a
+    /// Liquid error.
+    use super::LiquidReport;
+
+    #[test]
+    fn reads_the_position_of_parse_errors() {
+        let parser = ParserBuilder::with_stdlib().build().unwrap();
+        let message: String = parser.parse("a\n{% if %}").err().unwrap().to_string();
+        let report: LiquidReport = parse_liquid_message(&message);
+        assert_eq!(report.line, Some(2));
+        assert_eq!(report.column, Some(6));
+        assert_eq!(report.line_text, Some(String::from("{% if %}")));
+        assert_eq!(report.message, "Value expected.");
+        assert!(report.traces.is_empty());
+    }
+
+    #[test]
+    fn reads_the_context_of_render_errors() {
+        let parser = ParserBuilder::with_stdlib().build().unwrap();
+        let template = parser.parse("{% for x in (1..2) %}{{ missing }}{% endfor %}").unwrap();
+        let message: String = template.render(&object!({"known": 1})).err().unwrap().to_string();
+        let report: LiquidReport = parse_liquid_message(&message);
+        assert_eq!(report.message, "Unknown variable");
+        assert_eq!(report.line, None);
+        assert_eq!(report.context, vec![(String::from("requested variable"), String::from("missing"))]);
+        assert_eq!(report.traces.len(), 1);
+        assert!(report.traces[0].starts_with("{% for x in (1..2)"));
+    }
+
+    #[test]
+    fn keeps_unknown_messages() {
+        let report: LiquidReport = parse_liquid_message(&String::from("liquid: Something else"));
+        assert_eq!(report.message, "Something else");
+        assert!(report.context.is_empty());
+    }
+}
\ No newline at end of file
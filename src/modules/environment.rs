@@ -0,0 +1,184 @@
+/*
+Mandy by Alyx Shang.
+Licensed under the FSL v1.
+*/
+
+/// Importing the function to read
+/// environment variables.
+use std::env::var;
+
+/// Importing the "VarError"
+/// enum to tell an unset
+/// environment variable apart
+/// from an invalid one.
+use std::env::VarError;
+
+/// Importing the "OnceLock"
+/// structure to store the
+/// environment once per process.
+use std::sync::OnceLock;
+
+/// Importing the "PathBuf"
+/// structure from Rust's standard
+/// library.
+use std::path::PathBuf;
+
+/// Importing the "Value"
+/// enum from the "serde_json"
+/// crate to merge configuration
+/// files.
+use serde_json::Value;
+
+/// Importing Mandy's error-handling
+/// structure to handle errors.
+use super::err::MandyErr;
+
+/// Importing the structure that
+/// holds what is known about
+/// an error.
+use super::err::ErrInfo;
+
+/// The name of the environment
+/// for a live site.
+pub const PRODUCTION: &str = "production";
+
+/// The name of the environment
+/// for working on a site locally.
+pub const DEVELOPMENT: &str = "development";
+
+/// The name of the environment
+/// variable to select an environment.
+pub const ENV_VAR: &str = "MANDY_ENV";
+
+/// The environment a Mandy project
+/// is built for. This is set once
+/// by the CLI.
+static ENVIRONMENT: OnceLock<String> = OnceLock::new();
+
+/// Checks that the supplied name of an environment only contains lowercase
+/// letters, digits, dashes, and underscores, so that it can be part of the
+/// name of an overlay file. Returns a "Result" type with an empty closure.
+pub fn validate_environment(name: &String) -> Result<(), MandyErr>{
+    let is_valid: bool = !name.is_empty() && name
+        .chars()
+        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_');
+    if is_valid {
+        Ok(())
+    }
+    else {
+        let e: String = format!("The environment \"{}\" may only contain lowercase letters, digits, dashes, and underscores.", name);
+        Err::<(), MandyErr>(MandyErr::Config(Box::new(ErrInfo::new(&e.to_string()))))
+    }
+}
+
+/// Works out the environment to build for. The supplied name from the
+/// "--env" flag is used if there is one, followed by the "MANDY_ENV"
+/// environment variable, followed by the supplied default. Returns a
+/// "Result" type.
+pub fn resolve_environment(flag: &Option<String>, default: &str) -> Result<String, MandyErr>{
+    let name: String = match flag {
+        Some(name) => name.clone(),
+        None => match var(ENV_VAR){
+            Ok(name) => name,
+            Err(VarError::NotPresent) => default.to_string(),
+            Err(e) => return Err::<String, MandyErr>(MandyErr::Config(Box::new(ErrInfo::from_source(e))))
+        }
+    };
+    match validate_environment(&name){
+        Ok(_valid) => Ok(name),
+        Err(e) => Err::<String, MandyErr>(e)
+    }
+}
+
+/// Sets the environment to build for. This function can only be
+/// called once. Returns a "Result" type with an empty closure.
+pub fn set_environment(name: &String) -> Result<(), MandyErr>{
+    let _valid: () = validate_environment(name)?;
+    match ENVIRONMENT.set(name.clone()){
+        Ok(_set_op) => Ok(()),
+        Err(_name) => {
+            let e: String = "The environment has already been set.".to_string();
+            Err::<(), MandyErr>(MandyErr::Usage(Box::new(ErrInfo::new(&e.to_string()))))
+        }
+    }
+}
+
+/// Returns the environment to build for. If no environment has been
+/// set, it is worked out from the "MANDY_ENV" environment variable and
+/// defaults to "production". Returns a "Result" type.
+pub fn get_environment() -> Result<String, MandyErr>{
+    match ENVIRONMENT.get(){
+        Some(name) => Ok(name.clone()),
+        None => resolve_environment(&None, PRODUCTION)
+    }
+}
+
+/// Finds the overlay file for the supplied environment in a Mandy project,
+/// "config.<environment>.yml", "config.<environment>.yaml",
+/// "config.<environment>.json", or "config.<environment>.toml". Returns "None" if there is no overlay
+/// file. Returns an error if there is more than one, or if there
+/// is none for an environment other than "production" or "development", as
+/// such an environment is only defined by its overlay file. A "Result" type
+/// is returned.
+pub fn find_config_overlay(dir: &String, environment: &String) -> Result<Option<PathBuf>, MandyErr>{
+    let mut found: Vec<PathBuf> = Vec::new();
+    for ext in ["yml", "yaml", "json", "toml"] {
+        let mut overlay_buf: PathBuf = PathBuf::new();
+        overlay_buf.push(dir);
+        overlay_buf.push(format!("config.{}.{}", environment, ext));
+        if overlay_buf.exists(){
+            found.push(overlay_buf);
+        }
+    }
+    if found.len() > 1 {
+        let names: Vec<String> = found
+            .iter()
+            .map(|overlay_buf| format!("\"{}\"", overlay_buf.display()))
+            .collect();
+        let e: String = format!(
+            "The environment \"{}\" has more than one overlay file: {}. Only one may exist.",
+            environment,
+            names.join(", ")
+        );
+        return Err::<Option<PathBuf>, MandyErr>(MandyErr::Config(Box::new(ErrInfo::new(&e.to_string()))));
+    }
+    match found.pop(){
+        Some(overlay_buf) => Ok(Some(overlay_buf)),
+        None => {
+            if environment == PRODUCTION || environment == DEVELOPMENT {
+                Ok(None)
+            }
+            else {
+                let e: String = format!(
+                    "The environment \"{}\" is not defined. Create the file \"config.{}.yml\", \"config.{}.yaml\", \"config.{}.json\", or \"config.{}.toml\" in the directory \"{}\" to define it.",
+                    environment,
+                    environment,
+                    environment,
+                    environment,
+                    environment,
+                    dir
+                );
+                Err::<Option<PathBuf>, MandyErr>(MandyErr::Config(Box::new(ErrInfo::new(&e.to_string()))))
+            }
+        }
+    }
+}
+
+/// Merges the supplied overlay into the supplied configuration. Keys in
+/// the overlay replace the same keys in the configuration. Nested maps,
+/// such as "user_config", are merged key by key. Lists are replaced.
+pub fn merge_config_values(base: &mut Value, overlay: Value){
+    match (base, overlay) {
+        (Value::Object(base_map), Value::Object(overlay_map)) => {
+            for (key, value) in overlay_map {
+                match base_map.get_mut(&key){
+                    Some(existing) => merge_config_values(existing, value),
+                    None => {
+                        base_map.insert(key, value);
+                    }
+                };
+            }
+        },
+        (base, overlay) => *base = overlay
+    };
+}
\ No newline at end of file
@@ -19,45 +19,433 @@ use std::error::Error;
 /// "Formatter" trait.
 use std::fmt::Formatter;
 
+/// Importing the "PathBuf"
+/// structure to store the
+/// path of the file an error
+/// came from.
+use std::path::PathBuf;
+
+/// Importing the "Path"
+/// structure to accept the
+/// path of the file an error
+/// came from.
+use std::path::Path;
+
+/// Importing the "Arc" structure
+/// to share the underlying error
+/// between clones.
+use std::sync::Arc;
+
+/// What is known about an
+/// empty collection of errors.
+static EMPTY_INFO: ErrInfo = ErrInfo {
+    details: String::new(),
+    path: None,
+    line: None,
+    column: None,
+    snippet: None,
+    notes: Vec::new(),
+    source: None
+};
+
 /// A data structure for
-/// storing and handling errors.
-#[derive(Clone,Eq,PartialEq, Debug)]
-pub struct MandyErr {
-    pub details: String
+/// storing what is known
+/// about an error.
+#[derive(Clone, Debug)]
+pub struct ErrInfo {
+    pub details: String,
+    pub path: Option<PathBuf>,
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+    pub snippet: Option<String>,
+    pub notes: Vec<String>,
+    pub source: Option<Arc<dyn Error + Send + Sync>>
 }
 
 /// Implements functions
-/// for the "MandyErr"
+/// for the "ErrInfo"
 /// structure.
+impl ErrInfo {
+
+    /// Implements a function to create
+    /// a new instance of this data structure
+    /// from a message.
+    pub fn new(details: &str) -> ErrInfo {
+        ErrInfo {
+            details: details.to_owned(),
+            path: None,
+            line: None,
+            column: None,
+            snippet: None,
+            notes: Vec::new(),
+            source: None
+        }
+    }
+
+    /// Implements a function to create
+    /// a new instance of this data structure
+    /// from an underlying error.
+    pub fn from_source<E: Error + Send + Sync + 'static>(source: E) -> ErrInfo {
+        ErrInfo {
+            details: source.to_string(),
+            path: None,
+            line: None,
+            column: None,
+            snippet: None,
+            notes: Vec::new(),
+            source: Some(Arc::new(source))
+        }
+    }
+}
+
+/// An enum for storing and
+/// handling errors. Every variant
+/// describes the kind of operation
+/// an error came from and keeps what
+/// is known about the error on the heap,
+/// so that results stay small. The "Multiple"
+/// variant holds every error collected
+/// during a build.
+#[derive(Clone, Debug)]
+pub enum MandyErr {
+    Usage(Box<ErrInfo>),
+    Config(Box<ErrInfo>),
+    Content(Box<ErrInfo>),
+    Template(Box<ErrInfo>),
+    Sass(Box<ErrInfo>),
+    Io(Box<ErrInfo>),
+    Other(Box<ErrInfo>),
+    Multiple(Vec<MandyErr>)
+}
+
+/// Implements functions
+/// for the "MandyErr"
+/// enum.
 impl MandyErr {
 
     /// Implements a function to create
-    /// a new instance of this data structure.
+    /// a new error that does not belong
+    /// to any particular kind.
     pub fn new(details: &str) -> MandyErr {
-        MandyErr {
-            details: details.to_owned()
+        MandyErr::Other(Box::new(ErrInfo::new(details)))
+    }
+
+    /// Implements a function to combine
+    /// the supplied errors into one error.
+    /// Nested collections of errors are
+    /// flattened and a single error is
+    /// returned as it is. An empty list
+    /// of errors is an error of its own.
+    pub fn from_errors(errors: Vec<MandyErr>) -> MandyErr {
+        let mut flat: Vec<MandyErr> = Vec::new();
+        for error in errors {
+            match error {
+                MandyErr::Multiple(nested) => flat.extend(nested),
+                error => flat.push(error)
+            }
+        }
+        if flat.is_empty(){
+            MandyErr::new("An error occurred, but no details were reported.")
+        }
+        else if flat.len() == 1 {
+            flat.remove(0)
+        }
+        else {
+            MandyErr::Multiple(flat)
+        }
+    }
+
+    /// Implements a function to return
+    /// every single error this error holds.
+    pub fn errors(&self) -> Vec<&MandyErr> {
+        match self {
+            MandyErr::Multiple(errors) => errors.iter().flat_map(|error| error.errors()).collect(),
+            error => vec![error]
+        }
+    }
+
+    /// Implements a function to return
+    /// what is known about this error. For
+    /// a collection of errors, this is what
+    /// is known about the first one. Nothing
+    /// is known about an empty collection.
+    pub fn info(&self) -> &ErrInfo {
+        match self {
+            MandyErr::Usage(info) => info,
+            MandyErr::Config(info) => info,
+            MandyErr::Content(info) => info,
+            MandyErr::Template(info) => info,
+            MandyErr::Sass(info) => info,
+            MandyErr::Io(info) => info,
+            MandyErr::Other(info) => info,
+            MandyErr::Multiple(errors) => match errors.first(){
+                Some(error) => error.info(),
+                None => &EMPTY_INFO
+            }
+        }
+    }
+
+    /// Implements a function to return
+    /// a mutable reference to what is
+    /// known about this error. Returns
+    /// "None" for a collection of errors.
+    fn info_mut(&mut self) -> Option<&mut ErrInfo> {
+        match self {
+            MandyErr::Usage(info) => Some(info),
+            MandyErr::Config(info) => Some(info),
+            MandyErr::Content(info) => Some(info),
+            MandyErr::Template(info) => Some(info),
+            MandyErr::Sass(info) => Some(info),
+            MandyErr::Io(info) => Some(info),
+            MandyErr::Other(info) => Some(info),
+            MandyErr::Multiple(_) => None
         }
     }
 
+    /// Implements a function to attach
+    /// the path of the offending file. A
+    /// path that is already set is kept.
+    pub fn with_path(mut self, path: &Path) -> MandyErr {
+        match self.info_mut(){
+            Some(info) if info.path.is_none() => info.path = Some(path.to_path_buf()),
+            _ => {}
+        };
+        self
+    }
+
+    /// Implements a function to attach
+    /// the line and the column in the
+    /// offending file.
+    pub fn with_location(mut self, line: &usize, column: &usize) -> MandyErr {
+        if let Some(info) = self.info_mut() {
+            info.line = Some(*line);
+            info.column = Some(*column);
+        }
+        self
+    }
+
+    /// Implements a function to attach
+    /// a snippet of the supplied source
+    /// code around the line and the column
+    /// of this error. Nothing is attached
+    /// if the line is not known.
+    pub fn with_snippet(mut self, source: &str) -> MandyErr {
+        if let Some(info) = self.info_mut() {
+            if let Some(line) = info.line {
+                let column: usize = info.column.unwrap_or(1);
+                info.snippet = render_snippet(source, &line, &column, &1);
+            }
+        }
+        self
+    }
+
+    /// Implements a function to attach
+    /// a note that is printed below the
+    /// message of this error.
+    pub fn with_note(mut self, note: &str) -> MandyErr {
+        if let Some(info) = self.info_mut() {
+            info.notes.push(note.to_owned());
+        }
+        self
+    }
+
+    /// Implements a function to return
+    /// the exit code the binary exits with
+    /// for this kind of error. A collection
+    /// of errors exits with the code of its
+    /// first error and an empty collection
+    /// with the code for other errors.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            MandyErr::Other(_) => 1,
+            MandyErr::Usage(_) => 2,
+            MandyErr::Config(_) => 3,
+            MandyErr::Content(_) => 4,
+            MandyErr::Template(_) => 5,
+            MandyErr::Sass(_) => 6,
+            MandyErr::Io(_) => 7,
+            MandyErr::Multiple(errors) => match errors.first(){
+                Some(error) => error.exit_code(),
+                None => 1
+            }
+        }
+    }
     /// Implements a function to return
-    /// a string representation of this 
-    /// data structure.
+    /// a string representation of this
+    /// data structure. Kept for existing
+    /// callers, as the "Display" trait
+    /// does the same.
+    #[deprecated(note = "Use the \"Display\" implementation of \"MandyErr\" instead.")]
+    #[allow(clippy::inherent_to_string_shadow_display)]
     pub fn to_string(self) -> String {
-        self.details.to_string()
+        format!("{}", self)
     }
 }
 
 /// Implements the error trait.
 impl Error for MandyErr {
-    fn description(&self) -> &str {
-        &self.details
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match &self.info().source {
+            Some(source) => Some(source.as_ref()),
+            None => None
+        }
     }
 }
 
 /// Implements the Display trait
-/// for the "MandyErr" structure.
+/// for the "MandyErr" enum. The
+/// path, line, and column are
+/// printed before the message
+/// if they are known. A collection
+/// of errors is printed grouped by
+/// file.
 impl Display for MandyErr {
     fn fmt(&self, f: &mut Formatter) -> Result {
-        write!(f,"{}",self.details)
+        match self {
+            MandyErr::Multiple(_) => {
+                let mut errors: Vec<&MandyErr> = self.errors();
+                errors.sort_by(|a, b| {
+                    let a_info: &ErrInfo = a.info();
+                    let b_info: &ErrInfo = b.info();
+                    (&a_info.path, &a_info.line, &a_info.column).cmp(&(&b_info.path, &b_info.line, &b_info.column))
+                });
+                let mut current: Option<&PathBuf> = None;
+                for error in &errors {
+                    let info: &ErrInfo = error.info();
+                    if info.path.as_ref() != current {
+                        current = info.path.as_ref();
+                        if let Some(path) = current {
+                            writeln!(f, "{}:", path.display())?;
+                        }
+                    }
+                    let details: String = info.details.trim_end().replace("\n", "\n    ");
+                    match (&info.line, &info.column) {
+                        (Some(line), Some(column)) => write!(f, "  {}:{}: {}", line, column, details)?,
+                        (Some(line), None) => write!(f, "  {}: {}", line, details)?,
+                        _ => write!(f, "  {}", details)?
+                    };
+                    write_extras(f, info, "  ")?;
+                    writeln!(f)?;
+                }
+                write!(f, "{} errors were found.", errors.len())
+            },
+            _ => {
+                let info: &ErrInfo = self.info();
+                match (&info.path, &info.line, &info.column) {
+                    (Some(path), Some(line), Some(column)) => write!(f, "{}:{}:{}: {}", path.display(), line, column, info.details)?,
+                    (Some(path), Some(line), None) => write!(f, "{}:{}: {}", path.display(), line, info.details)?,
+                    (Some(path), _, _) => write!(f, "{}: {}", path.display(), info.details)?,
+                    _ => write!(f, "{}", info.details)?
+                };
+                write_extras(f, info, "")
+            }
+        }
+    }
+}
+
+/// Writes the snippet and the notes
+/// of an error on their own lines, each
+/// line starting with the supplied
+/// indentation.
+fn write_extras(f: &mut Formatter, info: &ErrInfo, indent: &str) -> Result {
+    if let Some(snippet) = &info.snippet {
+        for line in snippet.lines() {
+            write!(f, "\n{}{}", indent, line)?;
+        }
+    }
+    for note in &info.notes {
+        write!(f, "\n{}  = note: {}", indent, note)?;
+    }
+    Ok(())
+}
+
+/// Renders the line with the supplied number from the supplied source code
+/// with a caret under the supplied column. The caret is as wide as the supplied
+/// width, but never wider than the rest of the line. Returns "None" if the
+/// source code does not have a line with this number.
+pub fn render_snippet(source: &str, line: &usize, column: &usize, width: &usize) -> Option<String> {
+    let text: &str = source.lines().nth(line.checked_sub(1)?)?;
+    let number: String = line.to_string();
+    let gutter: String = " ".repeat(number.len());
+    let offset: usize = text.chars().take(column.saturating_sub(1)).map(|c| if c == '\t' { 4 } else { 1 }).sum();
+    let remaining: usize = text.chars().count().saturating_sub(column.saturating_sub(1)).max(1);
+    let carets: String = "^".repeat((*width).clamp(1, remaining));
+    Some(format!(
+        "{} |\n{} | {}\n{} | {}{}",
+        gutter,
+        number,
+        text.replace('\t', "    "),
+        gutter,
+        " ".repeat(offset),
+        carets
+    ))
+}
+
+/// Tests for collecting
+/// errors.
+#[cfg(test)]
+mod tests {
+
+    /// Importing the standard
+    /// "Error" trait.
+    use std::error::Error;
+
+    /// Importing the structure that
+    /// holds what is known about
+    /// an error.
+    use super::ErrInfo;
+
+    /// Importing Mandy's error-handling
+    /// structure to handle errors.
+    use super::MandyErr;
+
+    #[test]
+    fn flattens_collections_of_errors() {
+        let nested: MandyErr = MandyErr::Multiple(vec![
+            MandyErr::Config(Box::new(ErrInfo::new("a"))),
+            MandyErr::Content(Box::new(ErrInfo::new("b")))
+        ]);
+        let combined: MandyErr = MandyErr::from_errors(vec![nested, MandyErr::Sass(Box::new(ErrInfo::new("c")))]);
+        assert_eq!(combined.errors().len(), 3);
+        assert_eq!(combined.exit_code(), 3);
+        let single: MandyErr = MandyErr::from_errors(vec![MandyErr::Io(Box::new(ErrInfo::new("d")))]);
+        assert_eq!(single.exit_code(), 7);
+        assert_eq!(single.info().details, "d");
+    }
+
+    #[test]
+    fn handles_empty_collections_of_errors() {
+        let empty: MandyErr = MandyErr::Multiple(Vec::new());
+        assert_eq!(empty.info().details, "");
+        assert_eq!(empty.exit_code(), 1);
+        assert!(empty.source().is_none());
+        assert!(format!("{}", empty).contains("0 errors"));
+        let combined: MandyErr = MandyErr::from_errors(Vec::new());
+        assert_eq!(combined.exit_code(), 1);
+        assert!(!combined.info().details.is_empty());
+    }
+
+    #[test]
+    fn gives_every_kind_of_error_its_own_exit_code() {
+        let errors: Vec<MandyErr> = vec![
+            MandyErr::Other(Box::new(ErrInfo::new("other"))),
+            MandyErr::Usage(Box::new(ErrInfo::new("usage"))),
+            MandyErr::Config(Box::new(ErrInfo::new("config"))),
+            MandyErr::Content(Box::new(ErrInfo::new("content"))),
+            MandyErr::Template(Box::new(ErrInfo::new("template"))),
+            MandyErr::Sass(Box::new(ErrInfo::new("sass"))),
+            MandyErr::Io(Box::new(ErrInfo::new("io")))
+        ];
+        let codes: Vec<i32> = errors.iter().map(|error| error.exit_code()).collect();
+        assert_eq!(codes, vec![1, 2, 3, 4, 5, 6, 7]);
+    }
+
+    #[test]
+    fn keeps_errors_small() {
+        assert!(std::mem::size_of::<MandyErr>() <= 32);
+        let error: MandyErr = MandyErr::Config(Box::new(ErrInfo::new("bad option")));
+        #[allow(deprecated)]
+        let text: String = error.clone().to_string();
+        assert_eq!(text, format!("{}", error));
     }
 }
\ No newline at end of file
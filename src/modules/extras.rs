@@ -17,6 +17,15 @@ use std::path::PathBuf;
 /// to catch and handle errors.
 use super::err::MandyErr;
 
+/// Importing the structure that
+/// holds what is known about
+/// an error.
+use super::err::ErrInfo;
+
+/// Importing the function to report
+/// a written file.
+use super::logger::log_written;
+
 /// Importing the structure
 /// to build sitemaps.
 use super::units::SiteMap;
@@ -25,26 +34,21 @@ use super::units::SiteMap;
 /// to build URLs for sitemaps.
 use super::units::SiteMapUrl;
 
-/// Importing Rust's standard
-/// API for working with maps.
-use std::collections::HashMap;
-
-/// Importing the "MandyContent"
-/// structure to read content files
-/// written in the Markdown format.
-use super::units::MandyContent;
+/// Importing the "SiteInfo"
+/// structure to read information
+/// gathered on a Mandy project.
+use super::units::SiteInfo;
 
-/// Importing the function
-/// to read a Mandy project's 
-/// configuration settings.
-use super::gather::read_config;
+/// Importing the structure that holds all
+/// information on a configuration file in a
+/// a Mandy project.
+use super::units::ConfigFile;
 
-/// Attempts to generate a sitemap and a "robots.txt" file. If this fails, an error is returned.
-pub fn seo(dir: &String, content_files: &HashMap<PathBuf,MandyContent>) -> Result<(),MandyErr>{
-    let config = match read_config(dir){
-        Ok(config) => config,
-        Err(e) => return Err::<(), MandyErr>(MandyErr::new(&e.to_string()))
-    };
+/// Attempts to generate a sitemap and a "robots.txt" file from information already gathered
+/// on a Mandy project. Existing files are overwritten.
+/// If this fails, an error is returned.
+pub fn seo(dir: &String, data: &SiteInfo) -> Result<(),MandyErr>{
+    let config: &ConfigFile = &data.config;
     if config.contents.seo{
         let mut sitemap_buf: PathBuf = PathBuf::new();
         let mut robots_buf: PathBuf = PathBuf::new();
@@ -57,46 +61,46 @@ pub fn seo(dir: &String, content_files: &HashMap<PathBuf,MandyContent>) -> Resul
         robots_buf.push("robots.txt");
         dist_buf.push(dir);
         dist_buf.push(&config.contents.dist_dir);
-        let mut map_urls: Vec<SiteMapUrl> = Vec::new();
-        for (_key,value) in content_files.into_iter().enumerate(){
-            let (_path,content) = value;
+        let mut full_urls: Vec<String> = Vec::new();
+        for content in data.content_files.values(){
             let full_url: String = format!(
                 "{}{}", 
                 config.contents.tl_domain, 
                 content.url
             );
+            full_urls.push(full_url);
+        }
+        full_urls.sort();
+        let mut map_urls: Vec<SiteMapUrl> = Vec::new();
+        for full_url in full_urls {
             map_urls.push(SiteMapUrl{ url: full_url });
         }
         let sitemap: SiteMap = SiteMap{ urls: map_urls };
         let robots_txt: String = format!("User-Agent: *\nDisallow:\n\nSitemap: {}/sitemap.xml", &config.contents.tl_domain);
         if dist_buf.exists(){
-            if !(sitemap_buf.exists()) && !(robots_buf.exists()){
-                let _sm_create_op: () = match coutils::create_file(&sitemap_buf.display().to_string()){
-                    Ok(_sm_create_op) => _sm_create_op,
-                    Err(e) => return Err::<(), MandyErr>(MandyErr::new(&e.to_string()))
-                };
-                let _sm_write_op: () = match coutils::write_to_file(&sitemap_buf.display().to_string(), &sitemap.to_string()){
-                    Ok(_sm_write_op) => _sm_write_op,
-                    Err(e) => return Err::<(), MandyErr>(MandyErr::new(&e.to_string()))
-                };
-                let _robots_create_op: () = match coutils::create_file(&robots_buf.display().to_string()){
-                    Ok(_robots_create_op) => _robots_create_op,
-                    Err(e) => return Err::<(), MandyErr>(MandyErr::new(&e.to_string()))
-                };
-                let robots_write_op: () = match coutils::write_to_file(&robots_buf.display().to_string(), &robots_txt){
-                    Ok(robots_write_op) => robots_write_op,
-                    Err(e) => return Err::<(), MandyErr>(MandyErr::new(&e.to_string()))
-                };
-                Ok(robots_write_op)
-            }
-            else {
-                let e: String = format!("The files \"{}\" and \"{}\" already exist.", &sitemap_buf.display().to_string(), &robots_buf.display().to_string());
-                Err::<(), MandyErr>(MandyErr::new(&e.to_string()))
-            }
+            let _sm_create_op: () = match coutils::create_file(&sitemap_buf.display().to_string()){
+                Ok(_sm_create_op) => _sm_create_op,
+                Err(e) => return Err::<(), MandyErr>(MandyErr::Io(Box::new(ErrInfo::new(&e.to_string()))).with_path(&sitemap_buf))
+            };
+            let _sm_write_op: () = match coutils::write_to_file(&sitemap_buf.display().to_string(), &sitemap.to_string()){
+                Ok(_sm_write_op) => _sm_write_op,
+                Err(e) => return Err::<(), MandyErr>(MandyErr::Io(Box::new(ErrInfo::new(&e.to_string()))).with_path(&sitemap_buf))
+            };
+            log_written(&sitemap_buf, &data.staging);
+            let _robots_create_op: () = match coutils::create_file(&robots_buf.display().to_string()){
+                Ok(_robots_create_op) => _robots_create_op,
+                Err(e) => return Err::<(), MandyErr>(MandyErr::Io(Box::new(ErrInfo::new(&e.to_string()))).with_path(&robots_buf))
+            };
+            let robots_write_op: () = match coutils::write_to_file(&robots_buf.display().to_string(), &robots_txt){
+                Ok(robots_write_op) => robots_write_op,
+                Err(e) => return Err::<(), MandyErr>(MandyErr::Io(Box::new(ErrInfo::new(&e.to_string()))).with_path(&robots_buf))
+            };
+            log_written(&robots_buf, &data.staging);
+            Ok(robots_write_op)
         }
         else {
             let e: String = format!("The directory \"{}\" does not exist.", &dist_buf.display().to_string());
-            Err::<(), MandyErr>(MandyErr::new(&e.to_string()))
+            Err::<(), MandyErr>(MandyErr::Io(Box::new(ErrInfo::new(&e.to_string()))).with_path(&dist_buf))
         }
     }
     else {
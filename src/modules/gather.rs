@@ -12,20 +12,25 @@ use coutils;
 /// from walking a directory.
 use std::path::PathBuf;
 
+/// Importing the "Path"
+/// structure to borrow
+/// paths to files.
+use std::path::Path;
+
 /// Importing Mandy's error-handling
 /// structure to handle errors.
 use super::err::MandyErr;
 
+/// Importing the structure that
+/// holds what is known about
+/// an error.
+use super::err::ErrInfo;
+
 /// Importing the "SiteInfo"
 /// structure to store information
 /// about the Mandy project.
 use super::units::SiteInfo;
 
-/// Importing the enum to specify
-/// in which direction an insertion 
-/// should take place.
-use super::units::Direction;
-
 /// Importing the structure that holds all
 /// information on a configuration file in a
 /// a Mandy project.
@@ -46,6 +51,11 @@ use super::units::ComplexPath;
 /// for working with maps.
 use std::collections::HashMap;
 
+/// Importing the "Value"
+/// enum to hold the values
+/// of data files.
+use liquid::model::Value;
+
 /// Importing the structure that holds all
 /// configuration options set for a Mandy
 /// project.
@@ -67,6 +77,11 @@ use super::units::UserDataFile;
 /// name of a file.
 use super::utils::extract_stem;
 
+/// Importing the function to
+/// name a data file after its
+/// path in the data directory.
+use super::utils::get_data_name;
+
 /// Importing the structure to
 /// store information
 /// about parsed Markdown files.
@@ -81,6 +96,76 @@ use super::units::UserDataFileType;
 /// of the "MandyContent" structure.
 use super::processors::parse_document;
 
+/// Importing the name of the
+/// directory archetypes are
+/// stored in.
+use super::archetypes::ARCHETYPES_DIR;
+
+/// Importing the function to parse
+/// a YAML configuration file so it
+/// can be merged.
+use super::processors::parse_yml_config_value;
+
+/// Importing the function to parse
+/// a JSON configuration file so it
+/// can be merged.
+use super::processors::parse_json_config_value;
+
+/// Importing the function to render
+/// pages from data files.
+use super::generators::generate_pages;
+
+/// Importing the function to parse
+/// a JSON data file.
+use super::processors::parse_json_data;
+
+/// Importing the function to parse
+/// a TOML data file.
+use super::processors::parse_toml_data;
+
+/// Importing the function to parse
+/// a CSV data file.
+use super::processors::parse_csv_data;
+
+/// Importing the function to parse
+/// a TOML configuration file.
+use super::processors::parse_toml_config;
+
+/// Importing the function to parse
+/// a TOML configuration file so it
+/// can be merged.
+use super::processors::parse_toml_config_value;
+
+/// Importing the function to fill
+/// in options that were not set.
+use super::config::apply_config_defaults;
+
+/// Importing the function to check
+/// the options of a Mandy project.
+use super::config::validate_config;
+
+/// Importing the function to find
+/// options that are not known.
+use super::config::find_unknown_config_keys;
+
+/// Importing the function to find
+/// out the environment built for.
+use super::environment::get_environment;
+
+/// Importing the function to find
+/// the overlay file for an environment.
+use super::environment::find_config_overlay;
+
+/// Importing the function to point
+/// errors from applying an overlay file
+/// to the option at fault.
+use super::config::overlay_error;
+
+/// Importing the function to merge
+/// an overlay file into a configuration
+/// file.
+use super::environment::merge_config_values;
+
 /// Importing the function to parse
 /// YAML data files.
 use super::processors::parse_yml_data;
@@ -105,10 +190,10 @@ use super::utils::find_files_with_ending;
 /// of the "MandyConfig" structure.
 use super::processors::parse_json_config;
 
-/// Importing the function to insert an entity
-/// into an instance of the "PathBuf" structure
-/// at the given item.
-use super::utils::insert_entity_into_buf_at_item;
+/// Importing the function to move the path
+/// of a content file into the output
+/// directory.
+use super::utils::get_dist_path;
 
 /// Attempts to find all files ending in ".scss" in the project directory's "sass"
 /// directory. If this operation fails, an error is returned. A "Result" type is returned.
@@ -129,7 +214,7 @@ pub fn find_sass_files(dir: &String) -> Result<Option<PathBuf>,MandyErr> {
             "The SASS directory at the path \"{}\" exists but does not contain an \"index.scss\" file.", 
             &sass_buf.display().to_string()
         );
-        Err::<Option<PathBuf>,MandyErr>(MandyErr::new(&e.to_string()))
+        Err::<Option<PathBuf>,MandyErr>(MandyErr::Sass(Box::new(ErrInfo::new(&e.to_string()))))
     }
     else {
         Ok(None)
@@ -144,262 +229,376 @@ pub fn find_layout_files(dir: &String) -> Result<Vec<LayoutFile>, MandyErr> {
     layouts_buf.push(dir);
     layouts_buf.push("layouts");
     if layouts_buf.exists(){
-        let layouts_files: Option<Vec<PathBuf>> = match find_files_with_ending(&layouts_buf.display().to_string(), "liquid"){
-            Ok(layouts_files) => layouts_files,
-            Err(e) => return Err::<Vec<LayoutFile>, MandyErr>(MandyErr::new(&e.to_string()))
-        };
+        let layouts_files: Option<Vec<PathBuf>> = find_files_with_ending(&layouts_buf.display().to_string(), "liquid")?;
         let files: Vec<PathBuf> = match layouts_files {
             Some(files) => files,
             None => {
                 let e: String = format!("No layout files found at the following path: \"{}\"!", &layouts_buf.display().to_string());
-                return Err::<Vec<LayoutFile>, MandyErr>(MandyErr::new(&e.to_string())) 
+                return Err::<Vec<LayoutFile>, MandyErr>(MandyErr::Template(Box::new(ErrInfo::new(&e.to_string())))) 
             }
         };
+        let mut errors: Vec<MandyErr> = Vec::new();
         for file in files {
             let file_path: String = file.display().to_string();
             let stem: String = match extract_stem(&file_path){
                 Ok(stem) => stem,
-                Err(e) => return Err::<Vec<LayoutFile>, MandyErr>(MandyErr::new(&e.to_string()))
+                Err(e) => {
+                    errors.push(e);
+                    continue;
+                }
             };
             let contents: String = match coutils::read_file(&file_path){
                 Ok(contents) => contents,
-                Err(e) => return Err::<Vec<LayoutFile>, MandyErr>(MandyErr::new(&e.to_string()))
+                Err(e) => {
+                    errors.push(MandyErr::Io(Box::new(ErrInfo::new(&e.to_string()))).with_path(&file));
+                    continue;
+                }
             };
-            result.push(LayoutFile{ name: stem, path: file, contents: contents});
+            result.push(LayoutFile{ name: stem, path: file, contents});
+        }
+        if errors.is_empty(){
+            Ok(result)
+        }
+        else {
+            Err::<Vec<LayoutFile>, MandyErr>(MandyErr::from_errors(errors))
         }
-        Ok(result)
     }
     else {
         let e: String = format!("The directory for layouts \"{}\" does not exist.", &layouts_buf.display().to_string());
-        Err::<Vec<LayoutFile>, MandyErr>(MandyErr::new(&e.to_string()))
+        Err::<Vec<LayoutFile>, MandyErr>(MandyErr::Template(Box::new(ErrInfo::new(&e.to_string()))))
     }
     
 }
 
 /// Parses the configuration file in a Mandy project and returns an 
-/// instance of the "ConfigFile" structure. If this operation fails,
-/// an error is returned. A "Result" type is returned.
+/// instance of the "ConfigFile" structure. If the environment built for
+/// has an overlay file, such as "config.staging.yml", the options in the
+/// overlay file replace the ones in the configuration file. If this
+/// operation fails, an error is returned. A "Result" type is returned.
 pub fn read_config(dir: &String) -> Result<ConfigFile, MandyErr>{
-    let mut json_config_buf: PathBuf = PathBuf::new();
-    json_config_buf.push(dir);
-    json_config_buf.push("config.json");
-    let mut yml_config_buf: PathBuf = PathBuf::new();
-    yml_config_buf.push(dir);
-    yml_config_buf.push("config.yml");
-    if json_config_buf.exists(){
-        let src: String = match coutils::read_file(&json_config_buf.display().to_string()){
-            Ok(src) => src,
-            Err(e) => return Err::<ConfigFile, MandyErr>(MandyErr::new(&e.to_string()))
-        };
-        let config: MandyConfig = match parse_json_config(&src){
-            Ok(config) => config,
-            Err(e) => return Err::<ConfigFile, MandyErr>(MandyErr::new(&e.to_string()))
-        };
-        Ok(ConfigFile{ path: json_config_buf, file_type: UserDataFileType::JSON, contents: config})
-
+    let environment: String = get_environment()?;
+    let candidates: Vec<(&str, UserDataFileType)> = vec![
+        ("config.yml", UserDataFileType::YAML),
+        ("config.json", UserDataFileType::JSON),
+        ("config.toml", UserDataFileType::TOML)
+    ];
+    let mut found: Vec<(PathBuf, UserDataFileType)> = Vec::new();
+    for (name, file_type) in candidates {
+        let mut candidate_buf: PathBuf = PathBuf::new();
+        candidate_buf.push(dir);
+        candidate_buf.push(name);
+        if candidate_buf.exists(){
+            found.push((candidate_buf, file_type));
+        }
     }
-    else if yml_config_buf.exists(){
-        let src: String = match coutils::read_file(&yml_config_buf.display().to_string()){
-            Ok(src) => src,
-            Err(e) => return Err::<ConfigFile, MandyErr>(MandyErr::new(&e.to_string()))
-        };
-        let config: MandyConfig = match parse_yml_config(&src){
-            Ok(config) => config,
-            Err(e) => return Err::<ConfigFile, MandyErr>(MandyErr::new(&e.to_string()))
-        };
-        Ok(ConfigFile{ path: yml_config_buf, file_type: UserDataFileType::YAML, contents: config})
+    if found.len() > 1 {
+        let names: Vec<String> = found
+            .iter()
+            .map(|(config_buf, _file_type)| format!("\"{}\"", config_buf.display()))
+            .collect();
+        let e: String = format!(
+            "More than one config file found in the directory \"{}\": {}. Only one config file may exist.",
+            dir,
+            names.join(", ")
+        );
+        return Err::<ConfigFile, MandyErr>(MandyErr::Config(Box::new(ErrInfo::new(&e.to_string()))));
     }
-    else {
-        let e: String = format!("No config file found in the directory \"{}\".", dir);
-        Err::<ConfigFile, MandyErr>(MandyErr::new(&e.to_string()))
+    let (config_buf, file_type): (PathBuf, UserDataFileType) = match found.pop(){
+        Some(config_file) => config_file,
+        None => {
+            let e: String = format!("No config file found in the directory \"{}\".", dir);
+            return Err::<ConfigFile, MandyErr>(MandyErr::Config(Box::new(ErrInfo::new(&e.to_string()))));
+        }
+    };
+    let src: String = match coutils::read_file(&config_buf.display().to_string()){
+        Ok(src) => src,
+        Err(e) => return Err::<ConfigFile, MandyErr>(MandyErr::Io(Box::new(ErrInfo::new(&e.to_string()))).with_path(&config_buf))
+    };
+    let overlay: Option<PathBuf> = find_config_overlay(dir, &environment)?;
+    let mut sources: Vec<(PathBuf, String)> = vec![(config_buf.clone(), src.clone())];
+    let mut config_value: serde_json::Value = read_config_value(&config_buf, &src)?;
+    let mut unknown_keys: Vec<ErrInfo> = find_unknown_config_keys(&config_buf, &src, &config_value);
+    let mut config: MandyConfig = match &overlay {
+        Some(overlay_buf) => {
+            let overlay_src: String = match coutils::read_file(&overlay_buf.display().to_string()){
+                Ok(overlay_src) => overlay_src,
+                Err(e) => return Err::<ConfigFile, MandyErr>(MandyErr::Io(Box::new(ErrInfo::new(&e.to_string()))).with_path(overlay_buf))
+            };
+            let overlay_value: serde_json::Value = read_config_value(overlay_buf, &overlay_src)?;
+            unknown_keys.append(&mut find_unknown_config_keys(overlay_buf, &overlay_src, &overlay_value));
+            sources.push((overlay_buf.clone(), overlay_src));
+            let base_value: serde_json::Value = config_value.clone();
+            merge_config_values(&mut config_value, overlay_value);
+            match serde_json::from_value::<MandyConfig>(config_value.clone()){
+                Ok(config) => config,
+                Err(e) => return Err::<ConfigFile, MandyErr>(overlay_error(e, &base_value, &config_value, &sources))
+            }
+        },
+        None => {
+            let parsed: Result<MandyConfig, MandyErr> = match file_type {
+                UserDataFileType::JSON => parse_json_config(&src),
+                UserDataFileType::YAML => parse_yml_config(&src),
+                UserDataFileType::TOML => parse_toml_config(&src),
+                UserDataFileType::CSV => {
+                    let e: String = "Configuration files cannot be written in the CSV format.".to_string();
+                    Err::<MandyConfig, MandyErr>(MandyErr::Config(Box::new(ErrInfo::new(&e.to_string()))))
+                }
+            };
+            match parsed {
+                Ok(config) => config,
+                Err(e) => return Err::<ConfigFile, MandyErr>(e.with_path(&config_buf).with_snippet(&src))
+            }
+        }
+    };
+    apply_config_defaults(&mut config, &config_value);
+    let _validate_op: () = validate_config(&config, &sources)?;
+    Ok(ConfigFile{
+        path: config_buf,
+        file_type,
+        overlay,
+        environment,
+        unknown_keys,
+        contents: config
+    })
+}
+
+/// Parses the supplied source of a configuration file or an overlay file
+/// at the supplied path into a "Value" that can be merged with other
+/// configuration files. The format is chosen by the file extension.
+/// If this operation fails, an error is returned. A "Result" type is returned.
+pub fn read_config_value(path: &Path, src: &str) -> Result<serde_json::Value, MandyErr>{
+    let ext: String = match path.extension(){
+        Some(ext) => ext.to_string_lossy().to_string(),
+        None => String::new()
+    };
+    let parsed: Result<serde_json::Value, MandyErr> = match ext.as_str(){
+        "json" => parse_json_config_value(src),
+        "toml" => parse_toml_config_value(src),
+        _ => parse_yml_config_value(src)
+    };
+    match parsed {
+        Ok(value) => Ok(value),
+        Err(e) => Err::<serde_json::Value, MandyErr>(e.with_path(path).with_snippet(src))
     }
 }
 
-/// Reads the "$project_dir/data" directory for files in either
-/// JSON or YAML format and retrieves the data held in these files.
-/// If this operation fails, an error is returned.
+/// Reads the "$project_dir/data" directory for files in the JSON, YAML,
+/// TOML, or CSV format and retrieves the data held in these files. Files of all formats
+/// are merged into one namespace and every file is parsed with the parser
+/// for its own format. Two files with the same name in different formats
+/// are an error. Files in subdirectories are named after their path, for
+/// example "en/menu" for "data/en/menu.yml", and a data file cannot have
+/// the same name as a subdirectory. If this operation fails, an error is returned.
 pub fn read_data_files(dir: &String) -> Result<Option<HashMap<String,UserDataFile>>, MandyErr>{
     let mut data_buf: PathBuf = PathBuf::new();
-    let e: String = format!("The project's \"data\" directory cannot be empty.");
     data_buf.push(dir);
     data_buf.push("data");
-    if data_buf.exists(){
-        let pos_yaml_files: Option<Vec<PathBuf>> = match find_files_with_ending(&data_buf.display().to_string(), "yml"){
-            Ok(yaml_files) => yaml_files,
-            Err(e) => return Err::<Option<HashMap<String,UserDataFile>>, MandyErr>(MandyErr::new(&e.to_string()))
+    if !data_buf.exists(){
+        return Ok(None);
+    }
+    let endings: Vec<(&str, UserDataFileType)> = vec![
+        ("yml", UserDataFileType::YAML),
+        ("yaml", UserDataFileType::YAML),
+        ("json", UserDataFileType::JSON),
+        ("toml", UserDataFileType::TOML),
+        ("csv", UserDataFileType::CSV)
+    ];
+    let mut found: Vec<(PathBuf, UserDataFileType)> = Vec::new();
+    for (ending, file_type) in endings {
+        match find_files_with_ending(&data_buf.display().to_string(), ending){
+            Ok(Some(data_files)) => {
+                for data_file in data_files {
+                    found.push((data_file, file_type.clone()));
+                }
+            },
+            Ok(None) => {},
+            Err(e) => return Err::<Option<HashMap<String,UserDataFile>>, MandyErr>(e)
         };
-        let pos_json_files: Option<Vec<PathBuf>> = match find_files_with_ending(&data_buf.display().to_string(), "json"){
-            Ok(yaml_files) => yaml_files,
-            Err(e) => return Err::<Option<HashMap<String,UserDataFile>>, MandyErr>(MandyErr::new(&e.to_string()))
+    }
+    if found.is_empty(){
+        let e: String = "The project's \"data\" directory cannot be empty.".to_string();
+        return Err::<Option<HashMap<String,UserDataFile>>, MandyErr>(MandyErr::Content(Box::new(ErrInfo::new(&e.to_string()))));
+    }
+    found.sort_by(|a, b| a.0.cmp(&b.0));
+    let mut result: HashMap<String,UserDataFile> = HashMap::new();
+    let mut errors: Vec<MandyErr> = Vec::new();
+    for (data_file, file_type) in found {
+        let data_file_path: String = data_file.display().to_string();
+        let name: String = match get_data_name(&data_buf, &data_file){
+            Ok(name) => name,
+            Err(e) => {
+                errors.push(e);
+                continue;
+            }
         };
-
-
-        if pos_yaml_files.is_some(){
-            let mut result: HashMap<String,UserDataFile> = HashMap::new();
-            match pos_yaml_files{
-                Some(yaml_files) => {
-                    for yaml_file in yaml_files {
-                        let yaml_file_path: String = yaml_file.display().to_string();
-                        let stem: String = match extract_stem(&yaml_file_path){
-                            Ok(stem) => stem,
-                            Err(e) => return Err::<Option<HashMap<String,UserDataFile>>, MandyErr>(MandyErr::new(&e.to_string()))
-                        };
-                        let contents: String = match coutils::read_file(&yaml_file_path){
-                            Ok(contents) => contents,
-                            Err(e) => return Err::<Option<HashMap<String,UserDataFile>>, MandyErr>(MandyErr::new(&e.to_string()))
-                        };
-                        
-                        let deserialized: Vec<HashMap<String,String>> = match parse_yml_data(&contents){
-                            Ok(deserialized) => deserialized,
-                            Err(e) => return Err::<Option<HashMap<String,UserDataFile>>, MandyErr>(MandyErr::new(&e.to_string()))
-                        };
-                        
-                        result.insert(stem.clone(), UserDataFile { path: yaml_file, file_name: stem.clone(), file_type: UserDataFileType::YAML, contents: deserialized}); 
-                        
-                    }
-                    Ok(Some(result))
-                },
-                None => return Err::<Option<HashMap<String,UserDataFile>>, MandyErr>(MandyErr::new(&e.to_string()))
+        if let Some(existing) = result.get(&name) {
+            let e: String = format!(
+                "The data files \"{}\" and \"{}\" are both called \"{}\". Rename or remove one of them.",
+                existing.path.display(),
+                data_file.display(),
+                &name
+            );
+            errors.push(MandyErr::Content(Box::new(ErrInfo::new(&e.to_string()))).with_path(&data_file));
+            continue;
+        }
+        let contents: String = match coutils::read_file(&data_file_path){
+            Ok(contents) => contents,
+            Err(e) => {
+                errors.push(MandyErr::Io(Box::new(ErrInfo::new(&e.to_string()))).with_path(&data_file));
+                continue;
             }
-        }        
-        else if pos_json_files.is_some(){
-            let mut result: HashMap<String,UserDataFile> = HashMap::new();
-            match pos_json_files{
-                Some(json_files) => {
-                    for json_file in json_files{
-                        let json_file_path: String = json_file.display().to_string();
-                        let stem: String = match extract_stem(&json_file_path){
-                            Ok(stem) => stem,
-                            Err(e) => return Err::<Option<HashMap<String,UserDataFile>>, MandyErr>(MandyErr::new(&e.to_string()))
-                        };
-                        let contents: String = match coutils::read_file(&json_file_path){
-                            Ok(contents) => contents,
-                            Err(e) => return Err::<Option<HashMap<String,UserDataFile>>, MandyErr>(MandyErr::new(&e.to_string()))
-                        };
-                        let deserialized: Vec<HashMap<String,String>> = match parse_yml_data(&contents){
-                            Ok(deserialized) => deserialized,
-                            Err(e) => return Err::<Option<HashMap<String,UserDataFile>>, MandyErr>(MandyErr::new(&e.to_string()))
-                        };
-                        result.insert(stem.clone(), UserDataFile { path: json_file, file_name: stem.clone(), file_type: UserDataFileType::JSON, contents: deserialized});  
-                    }
-                    Ok(Some(result))
-                },
-                None => { return Err::<Option<HashMap<String,UserDataFile>>, MandyErr>(MandyErr::new(&e.to_string())) }
+        };
+        let parsed: Result<Value, MandyErr> = match file_type {
+            UserDataFileType::JSON => parse_json_data(&contents),
+            UserDataFileType::YAML => parse_yml_data(&contents),
+            UserDataFileType::TOML => parse_toml_data(&contents),
+            UserDataFileType::CSV => parse_csv_data(&contents)
+        };
+        let deserialized: Value = match parsed {
+            Ok(deserialized) => deserialized,
+            Err(e) => {
+                errors.push(e.with_path(&data_file).with_snippet(&contents));
+                continue;
             }
+        };
+        result.insert(name.clone(), UserDataFile { path: data_file, file_name: name.clone(), file_type, contents: deserialized});
+    }
+    let mut names: Vec<&String> = result.keys().collect();
+    names.sort();
+    for name in &names {
+        let prefix: String = format!("{}/", name);
+        if let Some(other) = names.iter().find(|other| other.starts_with(&prefix)) {
+            let e: String = format!(
+                "The data file \"{}\" and the directory holding \"{}\" are both called \"{}\". Rename or remove one of them.",
+                result[*name].path.display(),
+                result[*other].path.display(),
+                name
+            );
+            errors.push(MandyErr::Content(Box::new(ErrInfo::new(&e.to_string()))).with_path(&result[*name].path));
         }
-        else {
-            Err::<Option<HashMap<String,UserDataFile>>, MandyErr>(MandyErr::new(&e.to_string())) 
-        }
+    }
+    if errors.is_empty(){
+        Ok(Some(result))
     }
     else {
-        Ok(None)
+        Err::<Option<HashMap<String,UserDataFile>>, MandyErr>(MandyErr::from_errors(errors))
     }
 }
 
 /// Finds all the Markdown files in a Mandy project and parses the content
-/// of these files. If this operation fails, an error is returned. A "Result" type
+/// of these files. Archetypes in the "archetypes" directory are skipped, as
+/// they are templates for new content files. If this operation fails, an error is returned. A "Result" type
 /// is returned.
 pub fn find_markdown_files(
     dir: &String,
     config: &MandyConfig
 ) -> Result<HashMap<PathBuf, MandyContent>, MandyErr>{
     let mut result: HashMap<PathBuf,MandyContent> = HashMap::new();
-    let pos_md_files: Option<Vec<PathBuf>> = match find_files_with_ending(dir, "markdown"){
-        Ok(md_files) => md_files,
-        Err(e) => return Err::<HashMap<PathBuf,MandyContent>, MandyErr>(MandyErr::new(&e.to_string()))
-    };
+    let pos_md_files: Option<Vec<PathBuf>> = find_files_with_ending(dir, "markdown")?;
     let md_files: Vec<PathBuf> = match pos_md_files{
         Some(md_files) => md_files,
         None => {
             let e: String = format!("No files ending in \".markdown\" found at the path \"{}\".", dir);
-            return Err::<HashMap<PathBuf,MandyContent>, MandyErr>(MandyErr::new(&e.to_string()))
+            return Err::<HashMap<PathBuf,MandyContent>, MandyErr>(MandyErr::Content(Box::new(ErrInfo::new(&e.to_string()))))
         }
     };
+    let mut archetypes_buf: PathBuf = PathBuf::new();
+    archetypes_buf.push(dir);
+    archetypes_buf.push(ARCHETYPES_DIR);
+    let mut dist_buf: PathBuf = PathBuf::new();
+    dist_buf.push(dir);
+    dist_buf.push(&config.dist_dir);
+    let mut errors: Vec<MandyErr> = Vec::new();
     for md_file in md_files {
+        if md_file.starts_with(&archetypes_buf){
+            continue;
+        }
         let md_path: String = md_file.display().to_string();
         let contents: String = match coutils::read_file(&md_path){
             Ok(contents) => contents,
-            Err(e) => return Err::<HashMap<PathBuf,MandyContent>, MandyErr>(MandyErr::new(&e.to_string()))
+            Err(e) => {
+                errors.push(MandyErr::Io(Box::new(ErrInfo::new(&e.to_string()))).with_path(&md_file));
+                continue;
+            }
         };
-        let content_store: ContentStore = match parse_document(&contents){
+        let content_store: ContentStore = match parse_document(&contents, &config.markdown){
             Ok(mandy_content) => mandy_content,
-            Err(e) => return Err::<HashMap<PathBuf,MandyContent>, MandyErr>(MandyErr::new(&e.to_string()))
+            Err(e) => {
+                errors.push(e.with_path(&md_file).with_snippet(&contents));
+                continue;
+            }
         };
-        let modified_path: PathBuf = match insert_entity_into_buf_at_item(&md_file, &config.dist_dir, dir, &Direction::After){
+        let modified_path: PathBuf = match get_dist_path(dir, &config.dist_dir, &md_file){
             Ok(modified_path) => modified_path,
-            Err(e) => return Err::<HashMap<PathBuf, MandyContent>, MandyErr>(MandyErr::new(&e.to_string()))
+            Err(e) => {
+                errors.push(e);
+                continue;
+            }
         };
-        let complex_path: ComplexPath = match produce_complex_path(&modified_path, &config.dist_dir){
+        let complex_path: ComplexPath = match produce_complex_path(&modified_path, &dist_buf){
             Ok(complex_path) => complex_path,
-            Err(e) => return Err::<HashMap<PathBuf, MandyContent>, MandyErr>(MandyErr::new(&e.to_string()))
+            Err(e) => {
+                errors.push(e);
+                continue;
+            }
         };
         let mandy_content: MandyContent = MandyContent{ content: content_store.content, layout: content_store.layout, params: content_store.params, url: complex_path.web_link, path: complex_path.on_disk_html_url };
         result.insert(md_file.clone(), mandy_content);
         
     }
-    Ok(result)
+    if errors.is_empty(){
+        Ok(result)
+    }
+    else {
+        Err::<HashMap<PathBuf,MandyContent>, MandyErr>(MandyErr::from_errors(errors))
+    }
 }
 
-/// Finds all the Markdown files in a given Mandy project and the given directories containing
-/// loop content and parses the content of these files. If this operation fails, an error is returned. A "Result" type
-/// is returned.
-pub fn find_loop_content_files(
-    dir: &String, 
-    config: &MandyConfig
+/// Groups already parsed content files by the loop content directories they are
+/// in. The files of each directory keep the order in which they are found on disk.
+/// No file is read or parsed again. If this operation fails, an error is returned. 
+/// A "Result" type is returned.
+pub fn group_loop_content_files(
+    dir: &String,
+    config: &MandyConfig,
+    content_files: &HashMap<PathBuf, MandyContent>
 ) -> Result<Option<HashMap<String, Vec<MandyContent>>>, MandyErr>{
     if config.has_loop_content{
         let loop_content_dirs: Vec<String> = match config.loop_content_dirs.clone(){
             Some(loop_content_dirs) => loop_content_dirs,
-            None => { return Err::<Option<HashMap<String, Vec<MandyContent>>>,MandyErr>(MandyErr::new("The \"has_loop_content\" flag was set to \"true\" but directories containing such content were not specified.")) }
+            None => { return Err::<Option<HashMap<String, Vec<MandyContent>>>,MandyErr>(MandyErr::Config(Box::new(ErrInfo::new("The \"has_loop_content\" flag was set to \"true\" but directories containing such content were not specified.")))) }
         };
         let mut result: HashMap<String,Vec<MandyContent>> = HashMap::new();
-        for loop_content_dir in loop_content_dirs.clone(){
+        for loop_content_dir in loop_content_dirs{
             let mut loop_content_path_buf: PathBuf = PathBuf::new();
             loop_content_path_buf.push(dir);
             loop_content_path_buf.push(loop_content_dir.clone());
             let loop_content_dir_path: String = loop_content_path_buf.display().to_string();
-            let pos_md_files: Option<Vec<PathBuf>> = match find_files_with_ending(&loop_content_dir_path, "markdown"){
-                Ok(pos_md_files) => pos_md_files,
-                Err(e) => return Err::<Option<HashMap<String,Vec<MandyContent>>>, MandyErr>(MandyErr::new(&e.to_string()))
-            };
+            let pos_md_files: Option<Vec<PathBuf>> = find_files_with_ending(&loop_content_dir_path, "markdown")?;
             let md_files: Vec<PathBuf> = match pos_md_files{
                 Some(md_files) => md_files,
                 None => {
                     let e: String = format!("No files ending in \".markdown\" found at the path \"{}\".", &loop_content_dir_path);
-                    return Err::<Option<HashMap<String, Vec<MandyContent>>>, MandyErr>(MandyErr::new(&e.to_string()))
+                    return Err::<Option<HashMap<String, Vec<MandyContent>>>, MandyErr>(MandyErr::Content(Box::new(ErrInfo::new(&e.to_string()))))
                 }
             };
             let mut mandy_content_vec: Vec<MandyContent> = Vec::new();
             for md_file in md_files {
-                let md_path: String = md_file.display().to_string();
-                let contents: String = match coutils::read_file(&md_path){
-                    Ok(contents) => contents,
-                    Err(e) => return Err::<Option<HashMap<String,Vec<MandyContent>>>, MandyErr>(MandyErr::new(&e.to_string()))
-                };
-                let content_store: ContentStore = match parse_document(&contents){
-                    Ok(mandy_content) => mandy_content,
-                    Err(e) => return Err::<Option<HashMap<String,Vec<MandyContent>>>, MandyErr>(MandyErr::new(&e.to_string()))
-                };
-                let modified_path: PathBuf = match insert_entity_into_buf_at_item(&md_file, &config.dist_dir, dir, &Direction::After){
-                    Ok(modified_path) => modified_path,
-                    Err(e) => return Err::<Option<HashMap<String,Vec<MandyContent>>>, MandyErr>(MandyErr::new(&e.to_string()))
-                };
-                let complex_path: ComplexPath = match produce_complex_path(&modified_path, &config.dist_dir){
-                    Ok(complex_path) => complex_path,
-                    Err(e) => return Err::<Option<HashMap<String, Vec<MandyContent>>>, MandyErr>(MandyErr::new(&e.to_string()))
+                match content_files.get(&md_file){
+                    Some(mandy_content) => mandy_content_vec.push(mandy_content.clone()),
+                    None => {
+                        let e: String = format!("The content file \"{}\" was not parsed.", &md_file.display().to_string());
+                        return Err::<Option<HashMap<String, Vec<MandyContent>>>, MandyErr>(MandyErr::Content(Box::new(ErrInfo::new(&e.to_string()))))
+                    }
                 };
-                let mandy_content: MandyContent = MandyContent{ content: content_store.content, layout: content_store.layout, params: content_store.params, url: complex_path.web_link, path: complex_path.on_disk_html_url };
-                mandy_content_vec.push(mandy_content);
             }
-            result.insert(loop_content_dir.clone(), mandy_content_vec);
+            result.insert(loop_content_dir, mandy_content_vec);
         }
         Ok(Some(result))
     }
     else {
-        Ok( None )
+        Ok(None)
     }
-    
-    
 }
 
 /// Retrieves all the HTML template code in a Mandy project's "partials"
@@ -410,68 +609,289 @@ pub fn retrieve_partials(dir: &String) -> Result<HashMap<String, String>, MandyE
     partials_dir_buf.push("partials");
     if partials_dir_buf.exists(){
         let mut result: HashMap<String, String> = HashMap::new();
-        let pos_liquid_files: Option<Vec<PathBuf>> = match find_files_with_ending(&partials_dir_buf.display().to_string(), "liquid"){
-            Ok(liquid_files) => liquid_files,
-            Err(e) => return Err::<HashMap<String,String>, MandyErr>(MandyErr::new(&e.to_string()))
-        };
+        let pos_liquid_files: Option<Vec<PathBuf>> = find_files_with_ending(&partials_dir_buf.display().to_string(), "liquid")?;
         let liquid_files: Vec<PathBuf> = match pos_liquid_files{
             Some(liquid_files) => liquid_files,
             None => {
-                let e: String = format!("The directory containing partial templates cannot be empty.");
-                return Err::<HashMap<String,String>, MandyErr>(MandyErr::new(&e.to_string()))
+                let e: String = "The directory containing partial templates cannot be empty.".to_string();
+                return Err::<HashMap<String,String>, MandyErr>(MandyErr::Template(Box::new(ErrInfo::new(&e.to_string()))))
             }
         };
+        let mut errors: Vec<MandyErr> = Vec::new();
         for item in liquid_files {
             let contents: String = match coutils::read_file(&item.display().to_string()){
                 Ok(contents) => contents,
-                Err(e) => return Err::<HashMap<String,String>, MandyErr>(MandyErr::new(&e.to_string()))
+                Err(e) => {
+                    errors.push(MandyErr::Io(Box::new(ErrInfo::new(&e.to_string()))).with_path(&item));
+                    continue;
+                }
             };
             let stem: String = match extract_stem(&item.display().to_string()){
                 Ok(stem) => stem,
-                Err(e) => return Err::<HashMap<String,String>, MandyErr>(MandyErr::new(&e.to_string()))
+                Err(e) => {
+                    errors.push(e);
+                    continue;
+                }
             };
             result.insert(stem, contents);
 
         }
-        Ok(result)
+        if errors.is_empty(){
+            Ok(result)
+        }
+        else {
+            Err::<HashMap<String,String>, MandyErr>(MandyErr::from_errors(errors))
+        }
     }
     else {
-        let e: String = format!("The directory for partial templates was not found.");
-        Err::<HashMap<String,String>, MandyErr>(MandyErr::new(&e.to_string()))
+        let e: String = "The directory for partial templates was not found.".to_string();
+        Err::<HashMap<String,String>, MandyErr>(MandyErr::Template(Box::new(ErrInfo::new(&e.to_string()))))
     }
 }
 
 /// A function that gathers all the information on a Mandy project.
-/// If the operation is successful, an instance of the "SiteInfo" structure 
-/// is returned. If the operation fails, an error is returned.
+/// Pages from page generators are added to the content files.
+/// Every file in the project is read and parsed only once. Apart from
+/// the configuration file, a file that fails to be read or parsed does not
+/// stop the others from being gathered. If the operation is successful, an
+/// instance of the "SiteInfo" structure is returned. If the operation fails,
+/// an error holding every problem found is returned.
 pub fn gather_project_data(dir: &String) -> Result<SiteInfo, MandyErr> {
-    let config: ConfigFile = match read_config(dir){
-        Ok(config) => config,
-        Err(e) => return Err::<SiteInfo, MandyErr>(MandyErr::new(&e.to_string()))
-    };
-    let content_files: HashMap<PathBuf, MandyContent> = match find_markdown_files(dir, &config.contents){
+    let config: ConfigFile = read_config(dir)?;
+    let mut errors: Vec<MandyErr> = Vec::new();
+    let mut content_files: HashMap<PathBuf, MandyContent> = match find_markdown_files(dir, &config.contents){
         Ok(content_files) => content_files,
-        Err(e) => return Err::<SiteInfo, MandyErr>(MandyErr::new(&e.to_string()))
+        Err(e) => {
+            errors.push(e);
+            HashMap::new()
+        }
     };
     let data_files: Option<HashMap<String,UserDataFile>> = match read_data_files(dir){
         Ok(data_files) => data_files,
-        Err(e) => return Err::<SiteInfo, MandyErr>(MandyErr::new(&e.to_string()))
+        Err(e) => {
+            errors.push(e);
+            None
+        }
+    };
+    let loop_content: Option<HashMap<String, Vec<MandyContent>>> = if errors.is_empty(){
+        match group_loop_content_files(dir, &config.contents, &content_files){
+            Ok(loop_content) => loop_content,
+            Err(e) => {
+                errors.push(e);
+                None
+            }
+        }
+    }
+    else {
+        None
     };
-    let loop_content: Option<HashMap<String, Vec<MandyContent>>> = match find_loop_content_files(dir, &config.contents){
-        Ok(loop_content) => loop_content,
-        Err(e) => return Err::<SiteInfo, MandyErr>(MandyErr::new(&e.to_string()))
+    let generated_pages: HashMap<PathBuf, PathBuf> = if errors.is_empty(){
+        match generate_pages(dir, &config.contents, &data_files, &content_files){
+            Ok((pages, sources)) => {
+                content_files.extend(pages);
+                sources
+            },
+            Err(e) => {
+                errors.push(e);
+                HashMap::new()
+            }
+        }
+    }
+    else {
+        HashMap::new()
     };
     let layouts: Vec<LayoutFile> = match find_layout_files(dir){
         Ok(layouts) => layouts,
-        Err(e) => return Err::<SiteInfo, MandyErr>(MandyErr::new(&e.to_string()))
+        Err(e) => {
+            errors.push(e);
+            Vec::new()
+        }
     };
     let sass_files: Option<PathBuf> = match find_sass_files(dir){
         Ok(sass_files) => sass_files,
-        Err(e) => return Err::<SiteInfo, MandyErr>(MandyErr::new(&e.to_string()))
+        Err(e) => {
+            errors.push(e);
+            None
+        }
     };
     let partials: HashMap<String,String> = match retrieve_partials(dir){
         Ok(partials) => partials,
-        Err(e) => return Err::<SiteInfo, MandyErr>(MandyErr::new(&e.to_string()))
-    };        
-    Ok(SiteInfo { config: config, content_files: content_files, data_files: data_files, loop_content: loop_content, layouts: layouts, sass_dir: sass_files, partials: partials})
+        Err(e) => {
+            errors.push(e);
+            HashMap::new()
+        }
+    };
+    if !errors.is_empty(){
+        return Err::<SiteInfo, MandyErr>(MandyErr::from_errors(errors));
+    }
+    Ok(SiteInfo { config, content_files, generated_pages, data_files, loop_content, layouts, sass_dir: sass_files, partials, staging: None})
+}
+
+#[cfg(test)]
+mod tests {
+
+    /// Importing the "PathBuf"
+    /// structure from Rust's standard
+    /// library.
+    use std::path::PathBuf;
+
+    /// Importing the structure
+    /// for directories created
+    /// for tests.
+    use crate::modules::testing::TempDir;
+
+    /// Importing the configuration
+    /// file of a new project in YAML
+    /// format.
+    use crate::modules::scaffold::STARTER_YML_CONFIG;
+
+    /// Importing the configuration
+    /// file of a new project in TOML
+    /// format.
+    use crate::modules::scaffold::STARTER_TOML_CONFIG;
+
+    /// Importing the configuration
+    /// file of a new project in JSON
+    /// format.
+    use crate::modules::scaffold::STARTER_JSON_CONFIG;
+
+    /// Importing the enum to
+    /// describe the format of the
+    /// configuration file.
+    use crate::modules::units::UserDataFileType;
+
+    /// Importing the structure holding
+    /// information on the configuration
+    /// file.
+    use crate::modules::units::ConfigFile;
+
+    /// Importing Mandy's error-handling
+    /// structure to handle errors.
+    use crate::modules::err::MandyErr;
+
+    /// Importing the structure that
+    /// holds what is known about
+    /// an error.
+    use crate::modules::err::ErrInfo;
+
+    /// Importing the function to
+    /// read the configuration file.
+    use super::read_config;
+
+    /// Importing the function to
+    /// read the data files.
+    use super::read_data_files;
+
+    /// Importing the function to
+    /// gather everything known about
+    /// a project.
+    use super::gather_project_data;
+
+    /// Importing the "SiteInfo"
+    /// structure to store information
+    /// about the Mandy project.
+    use crate::modules::units::SiteInfo;
+
+    /// Importing the structure
+    /// holding a parsed content
+    /// file.
+    use crate::modules::units::MandyContent;
+
+    /// Importing the function
+    /// to create a new project.
+    use crate::modules::scaffold::init_project;
+
+    /// Importing the structure to
+    /// hold information on a data
+    /// file.
+    use crate::modules::units::UserDataFile;
+
+    /// Importing the "HashMap"
+    /// structure from Rust's standard
+    /// library.
+    use std::collections::HashMap;
+
+    #[test]
+    fn points_to_the_option_of_an_overlay_file_at_fault() {
+        let project: TempDir = TempDir::new("overlay-error");
+        project.write("config.yml", STARTER_YML_CONFIG);
+        let overlay_buf: PathBuf = project.write("config.production.yaml", "prod_url: https://staging.example.com\nseo: sometimes\n");
+        let error: MandyErr = match read_config(&project.dir()){
+            Err(error) => error,
+            Ok(_config) => panic!("An overlay file with an invalid option should not be read.")
+        };
+        let info: &ErrInfo = error.info();
+        assert_eq!(info.path.as_ref(), Some(&overlay_buf));
+        assert_eq!(info.line, Some(2));
+        assert!(info.snippet.as_ref().unwrap().contains("seo: sometimes"));
+    }
+
+    #[test]
+    fn reads_toml_configuration_files() {
+        let project: TempDir = TempDir::new("config-toml");
+        let config_buf: PathBuf = project.write("config.toml", STARTER_TOML_CONFIG);
+        let config: ConfigFile = read_config(&project.dir()).unwrap();
+        assert_eq!(config.path, config_buf);
+        assert!(matches!(config.file_type, UserDataFileType::TOML));
+        assert_eq!(config.contents.title, "My Mandy site");
+    }
+
+    #[test]
+    fn refuses_more_than_one_configuration_file() {
+        let project: TempDir = TempDir::new("config-many");
+        project.write("config.toml", STARTER_TOML_CONFIG);
+        project.write("config.json", STARTER_JSON_CONFIG);
+        let error: MandyErr = match read_config(&project.dir()){
+            Err(error) => error,
+            Ok(_config) => panic!("A project with two configuration files should not be read.")
+        };
+        assert!(matches!(error, MandyErr::Config(_)));
+        assert!(error.info().details.contains("config.toml"), "{}", error);
+        assert!(error.info().details.contains("config.json"), "{}", error);
+    }
+
+    #[test]
+    fn reads_data_files_of_every_format_together() {
+        let project: TempDir = TempDir::new("data-formats");
+        project.write("data/menu.yml", "- Home");
+        project.write("data/authors.json", "{\"ada\": \"Ada\"}");
+        project.write("data/links.yaml", "- About");
+        project.write("data/shop/prices.toml", "mug = 9");
+        let files: HashMap<String, UserDataFile> = read_data_files(&project.dir()).unwrap().unwrap();
+        let mut names: Vec<&String> = files.keys().collect();
+        names.sort();
+        assert_eq!(names, vec!["authors", "links", "menu", "shop/prices"]);
+        assert!(matches!(files.get("authors").unwrap().file_type, UserDataFileType::JSON));
+        assert!(matches!(files.get("links").unwrap().file_type, UserDataFileType::YAML));
+        assert!(matches!(files.get("menu").unwrap().file_type, UserDataFileType::YAML));
+        assert!(matches!(files.get("shop/prices").unwrap().file_type, UserDataFileType::TOML));
+    }
+
+    #[test]
+    fn refuses_data_files_with_the_same_name() {
+        let project: TempDir = TempDir::new("data-clash");
+        project.write("data/site.yml", "title: A");
+        project.write("data/site.json", "{\"title\": \"B\"}");
+        let error: MandyErr = match read_data_files(&project.dir()){
+            Err(error) => error,
+            Ok(_files) => panic!("Two data files with the same name should not be read.")
+        };
+        assert!(error.info().details.contains("site.yml"), "{}", error);
+        assert!(error.info().details.contains("site.json"), "{}", error);
+    }
+
+    #[test]
+    fn groups_loop_content_from_the_parsed_content_files() {
+        let project: TempDir = TempDir::new("gather-loop-content");
+        init_project(&project.dir(), &UserDataFileType::YAML).unwrap();
+        project.write("posts/second.markdown", "---\nlayout: default\ntitle: Second\n---\nSecond post.");
+        let data: SiteInfo = gather_project_data(&project.dir()).unwrap();
+        let posts: &Vec<MandyContent> = data.loop_content.as_ref().unwrap().get("posts").unwrap();
+        let mut urls: Vec<&String> = posts.iter().map(|post| &post.url).collect();
+        urls.sort();
+        assert_eq!(urls, vec!["/posts/hello-world/index.html", "/posts/second/index.html"]);
+        for post in posts {
+            assert!(data.content_files.values().any(|content| content.path == post.path && content.content == post.content));
+        }
+    }
 }
\ No newline at end of file
@@ -0,0 +1,382 @@
+/*
+Mandy by Alyx Shang.
+Licensed under the FSL v1.
+*/
+
+/// Importing the "PathBuf"
+/// structure from Rust's standard
+/// library.
+use std::path::PathBuf;
+
+/// Importing the "Path"
+/// structure to look at the
+/// file extensions of URLs.
+use std::path::Path;
+
+/// Importing Rust's standard API
+/// for working with maps.
+use std::collections::HashMap;
+
+/// Importing the "Value"
+/// enum to read the entries
+/// of data files.
+use liquid::model::Value;
+
+/// Importing the "Object"
+/// type to read the entries
+/// of data files.
+use liquid::model::Object;
+
+/// Importing the "ValueView"
+/// trait to read the fields
+/// of entries.
+use liquid::ValueView;
+
+/// Importing Mandy's error-handling
+/// structure to handle errors.
+use super::err::MandyErr;
+
+/// Importing the structure that
+/// holds what is known about
+/// an error.
+use super::err::ErrInfo;
+
+/// Importing the structure
+/// holding the options of a
+/// Mandy project.
+use super::units::MandyConfig;
+
+/// Importing the structure
+/// holding the options of a
+/// page generator.
+use super::units::PageGenerator;
+
+/// Importing the structure that
+/// holds a page of a Mandy project.
+use super::units::MandyContent;
+
+/// Importing the structure that
+/// holds a data file.
+use super::units::UserDataFile;
+
+/// Importing the structure that
+/// holds the path of a page on
+/// disk and its link.
+use super::units::ComplexPath;
+
+/// Importing the function to
+/// turn text into a slug.
+use super::archetypes::slugify;
+
+/// The placeholder in the URL
+/// pattern of a page generator
+/// that is replaced with the
+/// slug of every entry.
+pub const SLUG_PLACEHOLDER: &str = ":slug";
+
+/// The pages rendered by page
+/// generators together with the
+/// data file every page was
+/// generated from.
+pub type GeneratedPages = (HashMap<PathBuf, MandyContent>, HashMap<PathBuf, PathBuf>);
+
+/// Builds the "page" variable for the supplied generated page. The fields
+/// of its entry are set on the page itself and are also kept in "params".
+/// The "layout", "params", "content", "url", and "path" variables take
+/// precedence over fields of the same name.
+pub fn get_generated_page_object(page: &MandyContent) -> Object {
+    let mut result: Object = Object::new();
+    let mut params: Object = Object::new();
+    for (field, value) in &page.params {
+        result.insert(field.clone().into(), value.clone());
+        params.insert(field.clone().into(), value.clone());
+    }
+    result.insert("layout".into(), Value::scalar(page.layout.clone()));
+    result.insert("params".into(), Value::Object(params));
+    result.insert("content".into(), Value::scalar(page.content.clone()));
+    result.insert("url".into(), Value::scalar(page.url.clone()));
+    result.insert("path".into(), Value::scalar(page.path.clone()));
+    result
+}
+
+/// Returns the field of an entry
+/// used for its slug if the
+/// "slug_field" option is not set.
+pub fn default_slug_field() -> String {
+    String::from("slug")
+}
+
+/// Works out where the page for the supplied slug is written to and the link
+/// to it from the URL pattern of a page generator. URL patterns ending in "/"
+/// or in a segment without a file extension are written to an "index.html"
+/// file in a directory named after that segment, so that they are served
+/// as HTML.
+pub fn get_generated_path(dir: &String, dist_dir: &String, url: &str, slug: &str) -> ComplexPath {
+    let mut relative: String = url.replace(SLUG_PLACEHOLDER, slug).trim_start_matches('/').to_string();
+    let has_extension: bool = match url.rsplit('/').next(){
+        Some(last) => Path::new(last).extension().is_some(),
+        None => false
+    };
+    if relative.is_empty() || relative.ends_with('/'){
+        relative.push_str("index.html");
+    }
+    else if !has_extension {
+        relative.push_str("/index.html");
+    }
+    let mut html_buf: PathBuf = PathBuf::new();
+    html_buf.push(dir);
+    html_buf.push(dist_dir);
+    html_buf.push(&relative);
+    ComplexPath{ on_disk_html_url: html_buf.display().to_string(), web_link: format!("/{}", relative) }
+}
+
+/// Returns the entries of the supplied value of a data file. A list yields
+/// its maps and a map yields the maps it holds together with their keys,
+/// which are used as slugs for entries without a slug field. Returns "None"
+/// if the value holds anything else.
+pub fn get_generator_entries(value: &Value) -> Option<Vec<(Option<String>, &Object)>> {
+    let mut result: Vec<(Option<String>, &Object)> = Vec::new();
+    match value {
+        Value::Array(items) => {
+            for item in items {
+                match item {
+                    Value::Object(entry) => result.push((None, entry)),
+                    _ => return None
+                };
+            }
+        },
+        Value::Object(items) => {
+            let mut keys: Vec<&str> = items.keys().map(|key| key.as_str()).collect();
+            keys.sort();
+            for key in keys {
+                match items.get(key){
+                    Some(Value::Object(entry)) => result.push((Some(key.to_string()), entry)),
+                    _ => return None
+                };
+            }
+        },
+        _ => return None
+    };
+    Some(result)
+}
+
+/// Renders the pages of a single page generator from the supplied data files. The
+/// pages are keyed by the path of the data file followed by "#" and the slug of
+/// their entry. The fields of every entry are stored as the "params" of its page.
+/// Returns
+/// an error if the data file does not exist, does not hold entries, or if an entry
+/// has no slug. A "Result" type is returned.
+pub fn generate_pages_for(
+    dir: &String,
+    config: &MandyConfig,
+    generator: &PageGenerator,
+    data_files: &HashMap<String, UserDataFile>
+) -> Result<Vec<(PathBuf, MandyContent)>, MandyErr>{
+    let data_file: &UserDataFile = match data_files.get(&generator.data){
+        Some(data_file) => data_file,
+        None => {
+            let e: String = format!("The page generator for the layout \"{}\" uses the data file \"{}\", which does not exist.", &generator.layout, &generator.data);
+            return Err::<Vec<(PathBuf, MandyContent)>, MandyErr>(MandyErr::Config(Box::new(ErrInfo::new(&e.to_string()))));
+        }
+    };
+    let entries: Vec<(Option<String>, &Object)> = match get_generator_entries(&data_file.contents){
+        Some(entries) => entries,
+        None => {
+            let e: String = format!("The data file \"{}\" must hold a list of maps or a map of maps to generate pages from it.", &generator.data);
+            return Err::<Vec<(PathBuf, MandyContent)>, MandyErr>(MandyErr::Content(Box::new(ErrInfo::new(&e.to_string()))).with_path(&data_file.path));
+        }
+    };
+    let mut result: Vec<(PathBuf, MandyContent)> = Vec::new();
+    let mut errors: Vec<MandyErr> = Vec::new();
+    for (idx, (key, entry)) in entries.into_iter().enumerate() {
+        let mut params: HashMap<String, Value> = entry
+            .iter()
+            .map(|(field, value)| (field.to_string(), value.clone()))
+            .collect();
+        let slug_source: Option<String> = match entry.get(generator.slug_field.as_str()){
+            Some(Value::Scalar(scalar)) => Some(scalar.to_kstr().to_string()),
+            Some(_value) => None,
+            None => match &key {
+                Some(key) => {
+                    params.insert(generator.slug_field.clone(), Value::scalar(key.clone()));
+                    Some(key.clone())
+                },
+                None => None
+            }
+        };
+        let slug: String = match slug_source {
+            Some(slug_source) => slugify(&slug_source),
+            None => String::new()
+        };
+        if slug.is_empty(){
+            let entry_name: String = match &key {
+                Some(key) => format!("\"{}\"", key),
+                None => format!("{}", idx + 1)
+            };
+            let e: String = format!(
+                "The entry {} has no \"{}\" field with letters or digits to use in the URL of its page.",
+                entry_name,
+                &generator.slug_field
+            );
+            errors.push(MandyErr::Content(Box::new(ErrInfo::new(&e.to_string()))).with_path(&data_file.path));
+            continue;
+        }
+        let complex_path: ComplexPath = get_generated_path(dir, &config.dist_dir, &generator.url, &slug);
+        let page: MandyContent = MandyContent{
+            layout: generator.layout.clone(),
+            params,
+            content: String::new(),
+            url: complex_path.web_link,
+            path: complex_path.on_disk_html_url
+        };
+        result.push((PathBuf::from(format!("{}#{}", data_file.path.display(), slug)), page));
+    }
+    if errors.is_empty(){
+        Ok(result)
+    }
+    else {
+        Err::<Vec<(PathBuf, MandyContent)>, MandyErr>(MandyErr::from_errors(errors))
+    }
+}
+
+/// Renders the pages of every page generator in the supplied configuration from
+/// the supplied data files. Returns the generated pages and a map from the key of
+/// every generated page to the path of the data file it was generated from. Two pages
+/// written to the same path are an error, whether they were generated or come from
+/// the supplied content files. A "Result" type is returned.
+pub fn generate_pages(
+    dir: &String,
+    config: &MandyConfig,
+    data_files: &Option<HashMap<String, UserDataFile>>,
+    content_files: &HashMap<PathBuf, MandyContent>
+) -> Result<GeneratedPages, MandyErr>{
+    let mut pages: HashMap<PathBuf, MandyContent> = HashMap::new();
+    let mut sources: HashMap<PathBuf, PathBuf> = HashMap::new();
+    let generators: &Vec<PageGenerator> = match &config.generators {
+        Some(generators) => generators,
+        None => return Ok((pages, sources))
+    };
+    let empty: HashMap<String, UserDataFile> = HashMap::new();
+    let site_data: &HashMap<String, UserDataFile> = match data_files {
+        Some(site_data) => site_data,
+        None => &empty
+    };
+    let mut written: HashMap<String, PathBuf> = HashMap::new();
+    for (content_file_path, content) in content_files {
+        written.insert(content.path.clone(), content_file_path.clone());
+    }
+    let mut errors: Vec<MandyErr> = Vec::new();
+    for generator in generators {
+        let generated: Vec<(PathBuf, MandyContent)> = match generate_pages_for(dir, config, generator, site_data){
+            Ok(generated) => generated,
+            Err(e) => {
+                errors.push(e);
+                continue;
+            }
+        };
+        for (key, page) in generated {
+            let data_path: PathBuf = match site_data.get(&generator.data){
+                Some(data_file) => data_file.path.clone(),
+                None => continue
+            };
+            if let Some(existing) = written.get(&page.path) {
+                let e: String = format!(
+                    "The page \"{}\" generated from \"{}\" is written to the same path as the page from \"{}\".",
+                    &page.url,
+                    key.display(),
+                    existing.display()
+                );
+                errors.push(MandyErr::Content(Box::new(ErrInfo::new(&e.to_string()))).with_path(&data_path));
+                continue;
+            }
+            written.insert(page.path.clone(), key.clone());
+            sources.insert(key.clone(), data_path);
+            pages.insert(key, page);
+        }
+    }
+    if errors.is_empty(){
+        Ok((pages, sources))
+    }
+    else {
+        Err::<GeneratedPages, MandyErr>(MandyErr::from_errors(errors))
+    }
+}
+
+/// Tests for building
+/// generated pages.
+#[cfg(test)]
+mod tests {
+
+    /// Importing the "HashMap"
+    /// structure from Rust's standard
+    /// library.
+    use std::collections::HashMap;
+
+    /// Importing the "Value"
+    /// enum from the "liquid"
+    /// crate.
+    use liquid::model::Value;
+
+    /// Importing the "Object"
+    /// structure from the "liquid"
+    /// crate.
+    use liquid::model::Object;
+
+    /// Importing the structure
+    /// holding a page of
+    /// content.
+    use crate::modules::units::MandyContent;
+
+    /// Importing the structure
+    /// holding where a page is
+    /// written to and its link.
+    use crate::modules::units::ComplexPath;
+
+    /// Importing the function
+    /// to build the "page" variable
+    /// of generated pages.
+    use super::get_generated_page_object;
+
+    /// Importing the function
+    /// to work out where generated
+    /// pages are written to.
+    use super::get_generated_path;
+
+    #[test]
+    fn sets_entry_fields_on_the_page() {
+        let mut params: HashMap<String, Value> = HashMap::new();
+        params.insert(String::from("name"), Value::scalar("Ada".to_string()));
+        params.insert(String::from("url"), Value::scalar("https://ada.example".to_string()));
+        let page: MandyContent = MandyContent {
+            layout: String::from("person"),
+            params,
+            content: String::new(),
+            url: String::from("/people/ada"),
+            path: String::from("dist/people/ada.html")
+        };
+        let object: Object = get_generated_page_object(&page);
+        assert_eq!(object.get("name"), Some(&Value::scalar("Ada".to_string())));
+        assert_eq!(object.get("url"), Some(&Value::scalar("/people/ada".to_string())));
+        assert_eq!(object.get("layout"), Some(&Value::scalar("person".to_string())));
+        match object.get("params") {
+            Some(Value::Object(params)) => {
+                assert_eq!(params.get("name"), Some(&Value::scalar("Ada".to_string())));
+                assert_eq!(params.get("url"), Some(&Value::scalar("https://ada.example".to_string())));
+            },
+            _ => panic!("Generated pages should keep their entry in \"params\".")
+        };
+    }
+
+    #[test]
+    fn writes_generated_pages_after_their_url() {
+        let file: ComplexPath = get_generated_path(&String::from("site"), &String::from("dist"), &String::from("/people/:slug"), &String::from("ada"));
+        assert_eq!(file.web_link, "/people/ada/index.html");
+        assert!(file.on_disk_html_url.ends_with("dist/people/ada/index.html"));
+        let page: ComplexPath = get_generated_path(&String::from("site"), &String::from("dist"), &String::from("/people/:slug.html"), &String::from("ada"));
+        assert_eq!(page.web_link, "/people/ada.html");
+        assert!(page.on_disk_html_url.ends_with("dist/people/ada.html"));
+        let dir: ComplexPath = get_generated_path(&String::from("site"), &String::from("dist"), &String::from("/people/:slug/"), &String::from("ada"));
+        assert_eq!(dir.web_link, "/people/ada/index.html");
+        assert!(dir.on_disk_html_url.ends_with("dist/people/ada/index.html"));
+    }
+}
\ No newline at end of file
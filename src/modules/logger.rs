@@ -0,0 +1,350 @@
+/*
+Mandy by Alyx Shang.
+Licensed under the FSL v1.
+*/
+
+/// Importing the "json"
+/// macro from the "serde_json"
+/// crate to build log records.
+use serde_json::json;
+
+/// Importing the "Value"
+/// enum from the "serde_json"
+/// crate to hold log records.
+use serde_json::Value;
+
+/// Importing the "OnceLock"
+/// structure to store the options
+/// for logging once per process.
+use std::sync::OnceLock;
+
+/// Importing the "Instant"
+/// structure to time the stages
+/// of a build.
+use std::time::Instant;
+
+/// Importing the "PathBuf"
+/// structure from Rust's standard
+/// library.
+use std::path::PathBuf;
+
+/// Importing the "Path"
+/// structure from Rust's standard
+/// library.
+use std::path::Path;
+
+/// Importing Mandy's error-handling
+/// structure to handle errors.
+use super::err::MandyErr;
+
+/// Importing the structure that
+/// holds what is known about
+/// an error.
+use super::err::ErrInfo;
+
+/// An enum describing how much
+/// Mandy reports while it works.
+/// Errors are always reported.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+pub enum LogLevel {
+    Quiet,
+    Normal,
+    Verbose
+}
+
+/// An enum describing how log
+/// records are written.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LogFormat {
+    Text,
+    Json
+}
+
+/// A structure to hold the
+/// options for logging.
+#[derive(Debug, Clone, Copy)]
+pub struct LogOptions {
+    pub level: LogLevel,
+    pub format: LogFormat
+}
+
+/// The options for logging. These
+/// are set once by the CLI.
+static LOG_OPTIONS: OnceLock<LogOptions> = OnceLock::new();
+
+/// Sets the options for logging. This function can only be called once
+/// and must be called before anything is logged. Returns a "Result" type
+/// with an empty closure.
+pub fn set_log_options(options: &LogOptions) -> Result<(), MandyErr>{
+    match LOG_OPTIONS.set(*options){
+        Ok(_set_op) => Ok(()),
+        Err(_options) => {
+            let e: String = "The options for logging have already been set.".to_string();
+            Err::<(), MandyErr>(MandyErr::Usage(Box::new(ErrInfo::new(&e.to_string()))))
+        }
+    }
+}
+
+/// Returns the options for logging. If no options have been set,
+/// warnings are written as text.
+pub fn get_log_options() -> LogOptions {
+    match LOG_OPTIONS.get(){
+        Some(options) => *options,
+        None => LogOptions { level: LogLevel::Normal, format: LogFormat::Text }
+    }
+}
+
+/// Parses the name of a format for log records. Returns an error
+/// if the format is not known. A "Result" type is returned.
+pub fn parse_log_format(name: &String) -> Result<LogFormat, MandyErr>{
+    match name.as_str(){
+        "text" => Ok(LogFormat::Text),
+        "json" => Ok(LogFormat::Json),
+        _ => {
+            let e: String = format!("The log format must be either \"text\" or \"json\", not \"{}\".", name);
+            Err::<LogFormat, MandyErr>(MandyErr::Usage(Box::new(ErrInfo::new(&e.to_string()))))
+        }
+    }
+}
+
+/// Formats a single log record with the supplied options. Returns "None"
+/// if the level of the options is below the supplied level. Text records
+/// are formatted as the supplied text and JSON records as a single line
+/// holding the supplied record.
+pub fn format_record(options: &LogOptions, level: &LogLevel, text: &str, record: &Value) -> Option<String>{
+    if options.level < *level {
+        return None;
+    }
+    match options.format {
+        LogFormat::Text => Some(text.to_string()),
+        LogFormat::Json => Some(record.to_string())
+    }
+}
+
+/// Writes a single log record to stderr if the current level is at
+/// least the supplied level.
+fn emit(level: LogLevel, text: &str, record: &Value){
+    if let Some(line) = format_record(&get_log_options(), &level, text, record) {
+        eprintln!("{}", line);
+    }
+}
+
+/// Reports that the stage of a build with the supplied name has
+/// finished and how long it took since the supplied instant. Stages
+/// are only reported with the "--verbose" flag.
+pub fn log_stage(stage: &str, started: &Instant){
+    let elapsed = started.elapsed();
+    let text: String = format!("info: {} took {:.2?}", stage, elapsed);
+    let record: Value = json!({
+        "level": "info",
+        "event": "stage",
+        "stage": stage,
+        "duration_ms": elapsed.as_secs_f64() * 1000.0
+    });
+    emit(LogLevel::Verbose, &text, &record);
+}
+
+/// Returns the supplied path with the supplied staging directory replaced
+/// by the directory for the compiled project it stands in for. Paths
+/// outside the staging directory are returned as they are.
+pub fn get_output_path(path: &Path, staging: &Option<(PathBuf, PathBuf)>) -> PathBuf {
+    match staging {
+        Some((staging_buf, dist_buf)) => match path.strip_prefix(staging_buf){
+            Ok(relative) => dist_buf.join(relative),
+            Err(_e) => path.to_path_buf()
+        },
+        None => path.to_path_buf()
+    }
+}
+
+/// Reports that the file or directory at the supplied path has been
+/// written. Paths inside the supplied staging directory are reported as
+/// paths inside the directory for the compiled project it stands in for.
+/// Written files are only reported with the "--verbose" flag.
+pub fn log_written(path: &Path, staging: &Option<(PathBuf, PathBuf)>){
+    let output_path: String = get_output_path(path, staging).display().to_string();
+    let text: String = format!("info: wrote {}", output_path);
+    let record: Value = json!({
+        "level": "info",
+        "event": "write",
+        "path": output_path
+    });
+    emit(LogLevel::Verbose, &text, &record);
+}
+
+/// Reports the outcome of a command, such as a finished build or the
+/// address the development server listens on. Messages are reported
+/// unless the "--quiet" flag is used.
+pub fn log_info(message: &String){
+    let record: Value = json!({
+        "level": "info",
+        "event": "status",
+        "message": message
+    });
+    emit(LogLevel::Normal, message, &record);
+}
+
+/// Reports a problem that does not stop a build. Warnings are
+/// reported unless the "--quiet" flag is used.
+pub fn log_warning(warning: &ErrInfo){
+    let mut text: String = match (&warning.path, &warning.line, &warning.column) {
+        (Some(path), Some(line), Some(column)) => format!("warning: {}:{}:{}: {}", path.display(), line, column, warning.details),
+        (Some(path), Some(line), None) => format!("warning: {}:{}: {}", path.display(), line, warning.details),
+        (Some(path), _, _) => format!("warning: {}: {}", path.display(), warning.details),
+        _ => format!("warning: {}", warning.details)
+    };
+    for note in &warning.notes {
+        text.push_str(&format!("\n  = note: {}", note));
+    }
+    let record: Value = json!({
+        "level": "warning",
+        "message": warning.details,
+        "path": warning.path.as_ref().map(|path| path.display().to_string()),
+        "line": warning.line,
+        "column": warning.column,
+        "notes": warning.notes
+    });
+    emit(LogLevel::Normal, &text, &record);
+}
+
+/// Builds a JSON record for every single
+/// error in the supplied error.
+pub fn error_records(e: &MandyErr) -> Vec<Value>{
+    let mut result: Vec<Value> = Vec::new();
+    for error in e.errors() {
+        let info: &ErrInfo = error.info();
+        result.push(json!({
+            "level": "error",
+            "code": error.exit_code(),
+            "message": info.details,
+            "path": info.path.as_ref().map(|path| path.display().to_string()),
+            "line": info.line,
+            "column": info.column,
+            "notes": info.notes
+        }));
+    }
+    result
+}
+
+/// Reports an error. Errors are always reported. With the JSON
+/// format, every single error is written as its own record.
+pub fn log_error(e: &MandyErr){
+    match get_log_options().format {
+        LogFormat::Text => eprintln!("{}", e),
+        LogFormat::Json => {
+            for record in error_records(e) {
+                eprintln!("{}", record);
+            }
+        }
+    };
+}
+
+
+/// Tests for reporting what
+/// Mandy does while it works.
+#[cfg(test)]
+mod tests {
+
+    /// Importing the "PathBuf"
+    /// structure from Rust's standard
+    /// library.
+    use std::path::PathBuf;
+
+    /// Importing the function to report
+    /// paths inside a staging directory.
+    use super::get_output_path;
+
+    /// Importing the "json"
+    /// macro from the "serde_json"
+    /// crate to build log records.
+    use serde_json::json;
+
+    /// Importing the "Value"
+    /// enum from the "serde_json"
+    /// crate to hold log records.
+    use serde_json::Value;
+
+    /// Importing the enum describing
+    /// how much Mandy reports.
+    use super::LogLevel;
+
+    /// Importing the enum describing
+    /// how log records are written.
+    use super::LogFormat;
+
+    /// Importing the structure holding
+    /// the options for logging.
+    use super::LogOptions;
+
+    /// Importing the function to
+    /// format log records.
+    use super::format_record;
+
+    /// Importing the function to
+    /// build records for errors.
+    use super::error_records;
+
+    /// Importing the function to parse
+    /// the name of a log format.
+    use super::parse_log_format;
+
+    /// Importing Mandy's error-handling
+    /// structure to handle errors.
+    use crate::modules::err::MandyErr;
+
+    /// Importing the structure that
+    /// holds what is known about
+    /// an error.
+    use crate::modules::err::ErrInfo;
+
+    #[test]
+    fn replaces_only_the_supplied_staging_directory() {
+        let path: PathBuf = PathBuf::from("site/.dist-staging/.assets-staging/a.css");
+        assert_eq!(get_output_path(&path, &None), path);
+        let staging: Option<(PathBuf, PathBuf)> = Some((PathBuf::from("site/.dist-staging"), PathBuf::from("site/dist")));
+        assert_eq!(get_output_path(&path, &staging), PathBuf::from("site/dist/.assets-staging/a.css"));
+        assert_eq!(get_output_path(&PathBuf::from("other/.dist-staging/a.css"), &staging), PathBuf::from("other/.dist-staging/a.css"));
+    }
+
+    #[test]
+    fn reports_records_up_to_the_chosen_level() {
+        let text: String = String::from("info: wrote dist/index.html");
+        let record: Value = json!({ "level": "info", "event": "write", "path": "dist/index.html" });
+        let quiet: LogOptions = LogOptions { level: LogLevel::Quiet, format: LogFormat::Text };
+        assert_eq!(format_record(&quiet, &LogLevel::Normal, &text, &record), None);
+        assert_eq!(format_record(&quiet, &LogLevel::Verbose, &text, &record), None);
+        let normal: LogOptions = LogOptions { level: LogLevel::Normal, format: LogFormat::Text };
+        assert_eq!(format_record(&normal, &LogLevel::Normal, &text, &record), Some(text.clone()));
+        assert_eq!(format_record(&normal, &LogLevel::Verbose, &text, &record), None);
+        let verbose: LogOptions = LogOptions { level: LogLevel::Verbose, format: LogFormat::Text };
+        assert_eq!(format_record(&verbose, &LogLevel::Verbose, &text, &record), Some(text.clone()));
+    }
+
+    #[test]
+    fn writes_json_records_on_a_single_line() {
+        let text: String = String::from("info: build took 1ms");
+        let record: Value = json!({ "level": "info", "event": "stage", "stage": "build" });
+        let options: LogOptions = LogOptions { level: LogLevel::Verbose, format: LogFormat::Json };
+        let line: String = format_record(&options, &LogLevel::Verbose, &text, &record).unwrap();
+        assert!(!line.contains('\n'));
+        assert_eq!(serde_json::from_str::<Value>(&line).unwrap(), record);
+        assert_eq!(parse_log_format(&String::from("json")).unwrap(), LogFormat::Json);
+        assert_eq!(parse_log_format(&String::from("text")).unwrap(), LogFormat::Text);
+        assert!(parse_log_format(&String::from("xml")).is_err());
+    }
+
+    #[test]
+    fn writes_a_json_record_for_every_error() {
+        let errors: MandyErr = MandyErr::from_errors(vec![
+            MandyErr::Config(Box::new(ErrInfo::new("bad option"))).with_path(&PathBuf::from("config.yml")),
+            MandyErr::Sass(Box::new(ErrInfo::new("bad style")))
+        ]);
+        let records: Vec<Value> = error_records(&errors);
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0]["code"], json!(3));
+        assert_eq!(records[0]["path"], json!("config.yml"));
+        assert_eq!(records[1]["code"], json!(6));
+        assert_eq!(records[1]["message"], json!("bad style"));
+        assert_eq!(records[1]["path"], Value::Null);
+    }
+}
\ No newline at end of file
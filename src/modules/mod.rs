@@ -45,4 +45,67 @@ pub mod compile;
 /// that handles parsing and
 /// processing different formats
 /// of data.
-pub mod processors;
\ No newline at end of file
+pub mod processors;
+
+/// Exporting the module
+/// containing Mandy's
+/// development server.
+pub mod serve;
+
+/// Exporting the module
+/// to watch a Mandy project
+/// for changes.
+pub mod watch;
+
+/// Exporting the module
+/// containing Mandy's cache
+/// for incremental builds.
+pub mod cache;
+
+/// Exporting the module
+/// that turns errors into
+/// diagnostics pointing to
+/// the failing code.
+pub mod diagnostics;
+
+/// Exporting the module
+/// that reports what Mandy
+/// does while it works.
+pub mod logger;
+
+/// Exporting the module
+/// that finds problems that
+/// do not stop a build.
+pub mod warnings;
+
+/// Exporting the module
+/// that creates new Mandy
+/// projects.
+pub mod scaffold;
+
+/// Exporting the module
+/// that creates content
+/// files from archetypes.
+pub mod archetypes;
+
+/// Exporting the module
+/// that selects the environment
+/// a Mandy project is built for.
+pub mod environment;
+
+/// Exporting the module
+/// that fills in and checks
+/// the options of a Mandy
+/// project.
+pub mod config;
+
+/// Exporting the module
+/// that renders pages from
+/// data files.
+pub mod generators;
+
+/// Exporting the module
+/// with helpers shared by
+/// Mandy's tests.
+#[cfg(test)]
+pub mod testing;
\ No newline at end of file
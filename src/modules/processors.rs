@@ -11,6 +11,19 @@ use serde_yml;
 /// library to deserialize JSON data.
 use serde_json;
 
+/// Importing the "toml"
+/// library to deserialize TOML data.
+use toml;
+
+/// Importing the "csv"
+/// library to read CSV data.
+use csv;
+
+/// Importing the "Object"
+/// type from the "liquid" crate
+/// to build maps from data files.
+use liquid::model::Object;
+
 /// Importing the "object""
 /// macro from the "liquid"
 /// crate to supply context
@@ -22,6 +35,21 @@ use liquid::object;
 /// crate to parse templates.
 use liquid::Parser;
 
+/// Importing the "Value"
+/// enum from the "liquid" crate
+/// to hold structured values
+/// from front matter.
+use liquid::model::Value;
+
+/// Importing the "ValueView"
+/// trait to read values from
+/// front matter.
+use liquid::ValueView;
+
+/// Importing the function to find
+/// the line a key is set on.
+use super::config::find_config_key;
+
 /// Importing the "Template"
 /// structure from the "liquid"
 /// crate to render templates.
@@ -30,12 +58,66 @@ use liquid::Template;
 /// Importing the function to 
 /// parse Markdown code into
 /// HTML code.
-use markdown::to_html;
+use markdown::to_html_with_options;
+
+/// Importing the "Options"
+/// structure from the "markdown"
+/// crate to configure how Markdown
+/// is rendered.
+use markdown::Options;
+
+/// Importing the "ParseOptions"
+/// structure from the "markdown"
+/// crate to configure how Markdown
+/// is parsed.
+use markdown::ParseOptions;
+
+/// Importing the "CompileOptions"
+/// structure from the "markdown"
+/// crate to configure how Markdown
+/// is turned into HTML.
+use markdown::CompileOptions;
+
+/// Importing the "Constructs"
+/// structure from the "markdown"
+/// crate to turn Markdown syntax
+/// on or off.
+use markdown::Constructs;
+
+/// Importing the "Place" enum
+/// from the "markdown" crate to
+/// find out where an error occurred.
+use markdown::message::Place;
+
+/// Importing the structure
+/// holding the options for
+/// rendering Markdown.
+use super::units::MarkdownOptions;
+
+/// Importing the enum describing
+/// the type the fields of a column
+/// of a CSV file are read as.
+use super::units::CsvColumnType;
 
 /// Importing Mandy's error-handling
 /// structure to handle errors.
 use super::err::MandyErr;
 
+/// Importing the structure that
+/// holds what is known about
+/// an error.
+use super::err::ErrInfo;
+
+/// Importing the "PathBuf"
+/// structure from Rust's standard
+/// library.
+use std::path::PathBuf;
+
+/// Importing the function to turn
+/// errors from the "liquid" crate
+/// into diagnostics.
+use super::diagnostics::liquid_error;
+
 /// Importing the "ParserBuilder"
 /// structure from the "liquid"
 /// crate to parse Liquid code.
@@ -62,11 +144,6 @@ use super::units::MandyConfig;
 /// written in Markdown.
 use super::units::ContentStore;
 
-/// Importing the function to deserialize
-/// Markdown documents into a Rust
-/// data structure.
-use serde_frontmatter::deserialize;
-
 /// Importing the "EagerCompiler"
 /// entity to parse and process
 /// partial HTML templates.
@@ -78,33 +155,243 @@ use liquid::partials::EagerCompiler;
 use liquid::partials::InMemorySource;
 
 
+/// Turns an error from the "serde_yml" crate into an error of the supplied
+/// kind. The line and the column of the error are kept if they are known and
+/// removed from the message. The supplied number of lines is added to the line,
+/// for YAML code that does not start on the first line of its file.
+pub fn yml_error(kind: fn(Box<ErrInfo>) -> MandyErr, e: serde_yml::Error, line_offset: &usize) -> MandyErr {
+    let location: Option<serde_yml::Location> = e.location();
+    let mut info: ErrInfo = ErrInfo::from_source(e);
+    match location {
+        Some(location) => {
+            let suffix: String = format!(" at line {} column {}", location.line(), location.column());
+            info.details = info.details.replace(suffix.as_str(), "");
+            kind(Box::new(info)).with_location(&(location.line() + line_offset), &location.column())
+        },
+        None => kind(Box::new(info))
+    }
+}
+
+/// Turns an error from the "serde_json" crate into an error of the supplied
+/// kind. The line and the column of the error are kept and removed from
+/// the message.
+pub fn json_error(kind: fn(Box<ErrInfo>) -> MandyErr, e: serde_json::Error) -> MandyErr {
+    let line: usize = e.line();
+    let column: usize = e.column();
+    let suffix: String = format!(" at line {} column {}", line, column);
+    let mut info: ErrInfo = ErrInfo::from_source(e);
+    info.details = info.details.replace(suffix.as_str(), "");
+    kind(Box::new(info)).with_location(&line, &column)
+}
+
+/// Turns an error from the "toml" crate into an error of the supplied kind.
+/// The line and the column of the error are worked out from the position
+/// of the error in the supplied source code if it is known.
+pub fn toml_error(kind: fn(Box<ErrInfo>) -> MandyErr, e: toml::de::Error, src: &str) -> MandyErr {
+    let span: Option<std::ops::Range<usize>> = e.span();
+    let message: String = e.message().to_string();
+    let mut info: ErrInfo = ErrInfo::from_source(e);
+    info.details = message;
+    match span {
+        Some(span) => {
+            let before: &str = &src[..span.start.min(src.len())];
+            let line: usize = before.matches('\n').count() + 1;
+            let column: usize = match before.rfind('\n'){
+                Some(idx) => before[idx + 1..].chars().count() + 1,
+                None => before.chars().count() + 1
+            };
+            kind(Box::new(info)).with_location(&line, &column)
+        },
+        None => kind(Box::new(info))
+    }
+}
+
+/// Turns an error from the "csv" crate into an error of the supplied kind.
+/// The line of the error is kept if it is known.
+pub fn csv_error(kind: fn(Box<ErrInfo>) -> MandyErr, e: csv::Error) -> MandyErr {
+    let line: Option<u64> = e.position().map(|position| position.line());
+    let details: Option<String> = match e.kind() {
+        csv::ErrorKind::UnequalLengths { expected_len, len, .. } => Some(format!(
+            "This row has {} {}, but the header row has {}.",
+            len,
+            if *len == 1 { "field" } else { "fields" },
+            expected_len
+        )),
+        _ => None
+    };
+    let mut info: ErrInfo = ErrInfo::from_source(e);
+    if let Some(details) = details {
+        info.details = details;
+    }
+    match line {
+        Some(line) => kind(Box::new(info)).with_location(&(line as usize), &1),
+        None => kind(Box::new(info))
+    }
+}
+
 /// Parses and deserializes source from a JSON data file into a 
-/// "HashMap". Returns an error if this fails. A "Result" type is returned.
-pub fn parse_json_data(src: &String) -> Result<Vec<HashMap<String,String>>, MandyErr>{
-    let data: Vec<HashMap<String,String>> = match serde_json::from_str(src){
+/// "Value". Any JSON value is accepted and kept as it is. Returns
+/// an error if this fails. A "Result" type is returned.
+pub fn parse_json_data(src: &str) -> Result<Value, MandyErr>{
+    let data: Value = match serde_json::from_str(src){
         Ok(data) => data,
-        Err(e) => return Err::<Vec<HashMap<String,String>>, MandyErr>(MandyErr::new(&e.to_string()))
+        Err(e) => return Err::<Value, MandyErr>(json_error(MandyErr::Content, e))
     };
     Ok(data)
 }
 
 /// Parses and deserializes source from a YAML data file into a 
-/// "HashMap". Returns an error if this fails. A "Result" type is returned.
-pub fn parse_yml_data(src: &String) -> Result<Vec<HashMap<String,String>>, MandyErr>{
-    let data: Vec<HashMap<String, String>> = match serde_yml::from_str(src){
+/// "Value". Any YAML value is accepted and kept as it is. Returns
+/// an error if this fails. A "Result" type is returned.
+pub fn parse_yml_data(src: &str) -> Result<Value, MandyErr>{
+    let data: Value = match serde_yml::from_str(src){
         Ok(data) => data,
-        Err(e) => return Err::<Vec<HashMap<String, String>>, MandyErr>(MandyErr::new(&e.to_string()))
+        Err(e) => return Err::<Value, MandyErr>(yml_error(MandyErr::Content, e, &0))
     };
     Ok(data)
 }
 
+/// Turns a value from the "toml" crate into a "Value" for templates.
+/// Dates and times are turned into strings as they are written.
+pub fn toml_to_value(subject: toml::Value) -> Value {
+    match subject {
+        toml::Value::String(string) => Value::scalar(string),
+        toml::Value::Integer(integer) => Value::scalar(integer),
+        toml::Value::Float(float) => Value::scalar(float),
+        toml::Value::Boolean(boolean) => Value::scalar(boolean),
+        toml::Value::Datetime(datetime) => Value::scalar(datetime.to_string()),
+        toml::Value::Array(array) => Value::Array(array.into_iter().map(toml_to_value).collect()),
+        toml::Value::Table(table) => {
+            let mut object: Object = Object::new();
+            for (key, value) in table {
+                object.insert(key.into(), toml_to_value(value));
+            }
+            Value::Object(object)
+        }
+    }
+}
+
+/// Parses and deserializes source from a TOML data file into a 
+/// "Value". The table of the file is kept as it is. Returns an
+/// error if this fails. A "Result" type is returned.
+pub fn parse_toml_data(src: &str) -> Result<Value, MandyErr>{
+    let data: toml::Table = match toml::from_str(src){
+        Ok(data) => data,
+        Err(e) => return Err::<Value, MandyErr>(toml_error(MandyErr::Content, e, src))
+    };
+    Ok(toml_to_value(toml::Value::Table(data)))
+}
+
+/// Checks whether the supplied field of a CSV file is a number written
+/// out in decimal digits, with an optional sign and decimal point. Numbers
+/// with leading zeros, such as "007", are not, as they are usually codes.
+pub fn is_plain_number(field: &str) -> bool {
+    let unsigned: &str = field.strip_prefix('-').unwrap_or(field);
+    let (whole, fraction) = match unsigned.split_once('.'){
+        Some((whole, fraction)) => (whole, Some(fraction)),
+        None => (unsigned, None)
+    };
+    let is_digits = |part: &str| -> bool { !part.is_empty() && part.chars().all(|c| c.is_ascii_digit()) };
+    let has_valid_fraction: bool = match fraction {
+        Some(fraction) => is_digits(fraction),
+        None => true
+    };
+    is_digits(whole) && (whole == "0" || !whole.starts_with('0')) && has_valid_fraction
+}
+
+/// Returns the type the supplied field of a CSV file would be
+/// read as on its own.
+pub fn csv_field_type(field: &str) -> CsvColumnType {
+    if is_plain_number(field) && !field.contains('.') && field.parse::<i64>().is_ok(){
+        CsvColumnType::Integer
+    }
+    else if is_plain_number(field){
+        CsvColumnType::Float
+    }
+    else if field == "true" || field == "false" {
+        CsvColumnType::Boolean
+    }
+    else {
+        CsvColumnType::Text
+    }
+}
+
+/// Returns the type of a column holding fields of both supplied
+/// types. Whole numbers and decimal numbers make a column of decimal
+/// numbers and any other mix makes a column of strings.
+pub fn merge_csv_column_types(a: &CsvColumnType, b: &CsvColumnType) -> CsvColumnType {
+    match (a, b) {
+        (a, b) if a == b => *a,
+        (CsvColumnType::Integer, CsvColumnType::Float) => CsvColumnType::Float,
+        (CsvColumnType::Float, CsvColumnType::Integer) => CsvColumnType::Float,
+        _ => CsvColumnType::Text
+    }
+}
+
+/// Turns a single field of a CSV file into a "Value" of the supplied
+/// type of its column.
+pub fn csv_field_to_value(field: &str, column_type: &CsvColumnType) -> Value {
+    match column_type {
+        CsvColumnType::Integer => match field.parse::<i64>(){
+            Ok(integer) => Value::scalar(integer),
+            Err(_e) => Value::scalar(field.to_string())
+        },
+        CsvColumnType::Float => match field.parse::<f64>(){
+            Ok(float) => Value::scalar(float),
+            Err(_e) => Value::scalar(field.to_string())
+        },
+        CsvColumnType::Boolean => Value::scalar(field == "true"),
+        CsvColumnType::Text => Value::scalar(field.to_string())
+    }
+}
+
+/// Parses source from a CSV data file into a "Value". The first row
+/// holds the names of the columns and every other row becomes a map
+/// from these names to the fields of the row. Every column has a single
+/// type: a column becomes numbers or booleans only if all of its fields
+/// are numbers or booleans, so that one column never mixes types. Returns
+/// an error if this fails. A "Result" type is returned.
+pub fn parse_csv_data(src: &String) -> Result<Value, MandyErr>{
+    let mut reader: csv::Reader<&[u8]> = csv::Reader::from_reader(src.as_bytes());
+    let headers: csv::StringRecord = match reader.headers(){
+        Ok(headers) => headers.clone(),
+        Err(e) => return Err::<Value, MandyErr>(csv_error(MandyErr::Content, e))
+    };
+    let mut records: Vec<csv::StringRecord> = Vec::new();
+    let mut column_types: Vec<Option<CsvColumnType>> = vec![None; headers.len()];
+    for record in reader.records() {
+        let record: csv::StringRecord = match record {
+            Ok(record) => record,
+            Err(e) => return Err::<Value, MandyErr>(csv_error(MandyErr::Content, e))
+        };
+        for (column_type, field) in column_types.iter_mut().zip(record.iter()) {
+            let field_type: CsvColumnType = csv_field_type(field);
+            *column_type = match column_type {
+                Some(column_type) => Some(merge_csv_column_types(column_type, &field_type)),
+                None => Some(field_type)
+            };
+        }
+        records.push(record);
+    }
+    let mut rows: Vec<Value> = Vec::new();
+    for record in records {
+        let mut row: Object = Object::new();
+        for ((header, field), column_type) in headers.iter().zip(record.iter()).zip(column_types.iter()) {
+            let column_type: CsvColumnType = column_type.unwrap_or(CsvColumnType::Text);
+            row.insert(header.to_string().into(), csv_field_to_value(field, &column_type));
+        }
+        rows.push(Value::Object(row));
+    }
+    Ok(Value::Array(rows))
+}
+
 /// Parses and deserializes source from a YAML config file into an instance 
 /// of the "MandyConfig" structure. Returns an error if this fails. 
 /// A "Result" type is returned.
-pub fn parse_yml_config(src: &String) -> Result<MandyConfig, MandyErr>{
+pub fn parse_yml_config(src: &str) -> Result<MandyConfig, MandyErr>{
     let data: MandyConfig = match serde_yml::from_str(src){
         Ok(data) => data,
-        Err(e) => return Err::<MandyConfig, MandyErr>(MandyErr::new(&e.to_string()))
+        Err(e) => return Err::<MandyConfig, MandyErr>(yml_error(MandyErr::Config, e, &0))
     };
     Ok(data)
 }
@@ -112,56 +399,448 @@ pub fn parse_yml_config(src: &String) -> Result<MandyConfig, MandyErr>{
 /// Parses and deserializes source from a JSON config file into an instance 
 /// of the "MandyConfig" structure. Returns an error if this fails. 
 /// A "Result" type is returned.
-pub fn parse_json_config(src: &String) -> Result<MandyConfig, MandyErr>{
+pub fn parse_json_config(src: &str) -> Result<MandyConfig, MandyErr>{
     let data: MandyConfig = match serde_json::from_str(src){
         Ok(data) => data,
-        Err(e) => return Err::<MandyConfig, MandyErr>(MandyErr::new(&e.to_string()))
+        Err(e) => return Err::<MandyConfig, MandyErr>(json_error(MandyErr::Config, e))
     };
     Ok(data)
 }
 
-/// Parses and deserializes source from a Markdown content file into an instance 
-/// of the "ContentStore" structure. Returns an error if this fails. 
+/// Parses and deserializes source from a TOML config file into an instance 
+/// of the "MandyConfig" structure. Returns an error if this fails. 
 /// A "Result" type is returned.
-pub fn parse_document(src: &String) -> Result<ContentStore, MandyErr>{
-    let (data,content): (HashMap<String,String>,String) = match deserialize::<HashMap<String,String>>(src){
+pub fn parse_toml_config(src: &str) -> Result<MandyConfig, MandyErr>{
+    let data: MandyConfig = match toml::from_str(src){
         Ok(data) => data,
-        Err(_e) => {
-            let e: String = format!("Error parsing \"{}\".", src);
-            return Err::<ContentStore, MandyErr>(MandyErr::new(&e))
-        }
+        Err(e) => return Err::<MandyConfig, MandyErr>(toml_error(MandyErr::Config, e, src))
+    };
+    Ok(data)
+}
+
+/// Parses source from a TOML configuration file into a "Value" that can
+/// be merged with other configuration files. Returns an error if this fails.
+/// A "Result" type is returned.
+pub fn parse_toml_config_value(src: &str) -> Result<serde_json::Value, MandyErr>{
+    let data: serde_json::Value = match toml::from_str(src){
+        Ok(data) => data,
+        Err(e) => return Err::<serde_json::Value, MandyErr>(toml_error(MandyErr::Config, e, src))
+    };
+    Ok(data)
+}
+
+/// Parses source from a YAML configuration file into a "Value" that can
+/// be merged with other configuration files. Returns an error if this fails.
+/// A "Result" type is returned.
+pub fn parse_yml_config_value(src: &str) -> Result<serde_json::Value, MandyErr>{
+    let data: serde_json::Value = match serde_yml::from_str(src){
+        Ok(data) => data,
+        Err(e) => return Err::<serde_json::Value, MandyErr>(yml_error(MandyErr::Config, e, &0))
+    };
+    Ok(data)
+}
+
+/// Parses source from a JSON configuration file into a "Value" that can
+/// be merged with other configuration files. Returns an error if this fails.
+/// A "Result" type is returned.
+pub fn parse_json_config_value(src: &str) -> Result<serde_json::Value, MandyErr>{
+    let data: serde_json::Value = match serde_json::from_str(src){
+        Ok(data) => data,
+        Err(e) => return Err::<serde_json::Value, MandyErr>(json_error(MandyErr::Config, e))
     };
-    if data.contains_key("layout"){
-        Ok(ContentStore{ layout: data["layout"].clone(), params: data, content: to_html(&content) })
+    Ok(data)
+}
+
+/// Splits the source of a Markdown content file into its front matter
+/// and its Markdown content. The front matter has to be enclosed in two
+/// lines containing only "---". Returns the front matter, the Markdown
+/// content, the number of lines before the front matter, and the number
+/// of lines before the Markdown content. Returns "None" if the source
+/// does not start with front matter.
+pub fn split_front_matter(src: &str) -> Option<(String, String, usize, usize)> {
+    let lines: Vec<&str> = src.split('\n').collect();
+    let mut start: usize = 0;
+    while start < lines.len() && lines[start].trim().is_empty() {
+        start += 1;
     }
-    else {
-        let e: String = format!("The \"layout\" variable was not set in the following Markdown source code: \"{}\".", src);
-        Err::<ContentStore, MandyErr>(MandyErr::new(&e))
+    if start >= lines.len() || lines[start].trim() != "---" {
+        return None;
+    }
+    let mut end: usize = start + 1;
+    while end < lines.len() && lines[end].trim() != "---" {
+        end += 1;
     }
+    if end >= lines.len() {
+        return None;
+    }
+    let front_matter: String = lines[start + 1..end].join("\n");
+    let content: String = lines[end + 1..].join("\n");
+    Some((front_matter, content, start + 1, end + 1))
 }
 
-/// Processes Liquid code with an instance
-/// of the "SiteContext" structure and outputs HTML code.
-/// Returns an error if this operation fails.
+/// Turns the supplied options for rendering Markdown from the configuration
+/// file into options for the "markdown" crate. GitHub-flavored constructs
+/// that are not set on their own follow the "gfm" option.
+pub fn build_markdown_options(options: &MarkdownOptions) -> Options {
+    let gfm: bool = options.gfm;
+    let footnotes: bool = options.footnotes.unwrap_or(gfm);
+    let constructs: Constructs = Constructs {
+        gfm_table: options.tables.unwrap_or(gfm),
+        gfm_strikethrough: options.strikethrough.unwrap_or(gfm),
+        gfm_task_list_item: options.task_lists.unwrap_or(gfm),
+        gfm_autolink_literal: options.autolinks.unwrap_or(gfm),
+        gfm_footnote_definition: footnotes,
+        gfm_label_start_footnote: footnotes,
+        math_flow: options.math,
+        math_text: options.math,
+        ..Constructs::default()
+    };
+    let compile: CompileOptions = CompileOptions {
+        allow_dangerous_html: options.raw_html,
+        gfm_tagfilter: gfm,
+        ..CompileOptions::default()
+    };
+    Options {
+        parse: ParseOptions { constructs, ..ParseOptions::default() },
+        compile
+    }
+}
+
+/// Renders the supplied Markdown code into HTML with the supplied options.
+/// The supplied number of lines is added to the line of any error, for
+/// Markdown code that does not start on the first line of its file.
+/// Returns a "Result" type.
+pub fn render_markdown(src: &str, options: &Options, line_offset: &usize) -> Result<String, MandyErr>{
+    match to_html_with_options(src, options){
+        Ok(html) => Ok(html),
+        Err(message) => {
+            let location: Option<(usize, usize)> = match message.place.as_deref(){
+                Some(Place::Point(point)) => Some((point.line, point.column)),
+                Some(Place::Position(position)) => Some((position.start.line, position.start.column)),
+                None => None
+            };
+            let error: MandyErr = MandyErr::Content(Box::new(ErrInfo::new(&message.reason)));
+            match location {
+                Some((line, column)) => Err::<String, MandyErr>(error.with_location(&(line + line_offset), &column)),
+                None => Err::<String, MandyErr>(error)
+            }
+        }
+    }
+}
+
+/// Parses and deserializes source from a Markdown content file into an instance 
+/// of the "ContentStore" structure. Returns an error if this fails. Errors in the
+/// front matter carry the line and the column they occurred at in the content file.
 /// A "Result" type is returned.
-pub fn process_liquid(liquid_code: &String, ctx: &SiteContext, partials: &HashMap<String,String>) -> Result<String, MandyErr>{
+pub fn parse_document(src: &str, options: &MarkdownOptions) -> Result<ContentStore, MandyErr>{
+    let (front_matter, content, line_offset, content_offset): (String, String, usize, usize) = match split_front_matter(src){
+        Some(parts) => parts,
+        None => {
+            let e: String = "The content file does not start with front matter enclosed in lines containing only \"---\".".to_string();
+            return Err::<ContentStore, MandyErr>(MandyErr::Content(Box::new(ErrInfo::new(&e))).with_location(&1, &1))
+        }
+    };
+    let data: HashMap<String,Value> = match serde_yml::from_str(&front_matter){
+        Ok(data) => data,
+        Err(e) => return Err::<ContentStore, MandyErr>(yml_error(MandyErr::Content, e, &line_offset))
+    };
+    match data.get("layout"){
+        Some(Value::Scalar(layout)) => {
+            let layout: String = layout.to_kstr().to_string();
+            let html: String = render_markdown(&content, &build_markdown_options(options), &content_offset)?;
+            Ok(ContentStore{ layout, params: data, content: html })
+        },
+        Some(layout) => {
+            let e: String = format!("The \"layout\" variable must be a string naming a layout, but it is of the type \"{}\".", layout.type_name());
+            let error: MandyErr = MandyErr::Content(Box::new(ErrInfo::new(&e)));
+            match find_config_key(&front_matter, "layout"){
+                Some((line, column)) => Err::<ContentStore, MandyErr>(error.with_location(&(line + line_offset), &column)),
+                None => Err::<ContentStore, MandyErr>(error)
+            }
+        },
+        None => {
+            let e: String = "The \"layout\" variable was not set in the front matter.".to_string();
+            Err::<ContentStore, MandyErr>(MandyErr::Content(Box::new(ErrInfo::new(&e))))
+        }
+    }
+}
+
+/// Builds a parser for Liquid code that knows all the supplied partial templates.
+/// The partial templates are compiled once when the parser is built. The supplied
+/// paths and code of the partial templates are used to point to the failing code
+/// if this operation fails. A "Result" type is returned.
+pub fn build_parser(
+    partials: &HashMap<String,String>,
+    sources: &[(PathBuf, String)]
+) -> Result<Parser, MandyErr>{
     type Partials =  EagerCompiler<InMemorySource>;
     let mut partial_source = Partials::empty();
-    for (k,v) in partials.into_iter() {
+    for (k,v) in partials.iter() {
         partial_source.add(k,v);
     }
     let parser: Parser = match ParserBuilder::with_stdlib().partials(partial_source).build(){
         Ok(parser) => parser,
-        Err(e) => return Err::<String, MandyErr>(MandyErr::new(&e.to_string()))
-    };
-    let parsed: Template = match parser.parse(liquid_code){
-        Ok(parsed) => parsed,
-        Err(e) => return Err::<String, MandyErr>(MandyErr::new(&e.to_string()))
+        Err(e) => return Err::<Parser, MandyErr>(liquid_error(e, sources, None))
     };
+    Ok(parser)
+}
+
+/// Renders a parsed Liquid template with an instance of the
+/// "SiteContext" structure and outputs HTML code. The supplied paths
+/// and code of the layout and the partial templates, and the path of the
+/// content file being rendered, are used to point to the failing code
+/// if this operation fails. A "Result" type is returned.
+pub fn render_template(
+    template: &Template,
+    ctx: &SiteContext,
+    sources: &[(PathBuf, String)],
+    rendering: Option<&PathBuf>
+) -> Result<String, MandyErr>{
     let globals = object!(ctx);
-    let html: String = match parsed.render(&globals){
+    let html: String = match template.render(&globals){
         Ok(html) => html,
-        Err(e) => return Err::<String, MandyErr>(MandyErr::new(&e.to_string()))
+        Err(e) => return Err::<String, MandyErr>(liquid_error(e, sources, rendering))
     };
     Ok(html)
+}
+
+/// Processes Liquid code with an instance
+/// of the "SiteContext" structure and outputs HTML code.
+/// Returns an error if this operation fails.
+/// A "Result" type is returned.
+pub fn process_liquid(liquid_code: &str, ctx: &SiteContext, partials: &HashMap<String,String>) -> Result<String, MandyErr>{
+    let sources: Vec<(PathBuf, String)> = vec![(PathBuf::new(), liquid_code.to_string())];
+    let parser: Parser = build_parser(partials, &Vec::new())?;
+    let parsed: Template = match parser.parse(liquid_code){
+        Ok(parsed) => parsed,
+        Err(e) => return Err::<String, MandyErr>(liquid_error(e, &sources, None))
+    };
+    render_template(&parsed, ctx, &sources, None)
+}
+
+/// Tests for parsing the
+/// sources of a project.
+#[cfg(test)]
+mod tests {
+
+    /// Importing the function
+    /// to split front matter
+    /// from content.
+    use super::split_front_matter;
+
+    /// Importing the function
+    /// to find the type of a
+    /// CSV field.
+    use super::csv_field_type;
+
+    /// Importing the enum describing
+    /// the type of a CSV column.
+    use crate::modules::units::CsvColumnType;
+
+    /// Importing the function
+    /// to parse CSV data files.
+    use super::parse_csv_data;
+
+    /// Importing the "Value"
+    /// enum from the "liquid"
+    /// crate.
+    use super::Value;
+
+    /// Importing the "Object"
+    /// structure from the "liquid"
+    /// crate.
+    use super::Object;
+
+    /// Importing the "ParserBuilder"
+    /// structure from the "liquid"
+    /// crate to render templates.
+    use liquid::ParserBuilder;
+
+    /// Importing the "Template"
+    /// structure from the "liquid"
+    /// crate to render templates.
+    use super::Template;
+
+    /// Importing the function
+    /// to parse content files.
+    use super::parse_document;
+
+    /// Importing the function
+    /// to parse YAML data files.
+    use super::parse_yml_data;
+
+    /// Importing the function
+    /// to parse JSON data files.
+    use super::parse_json_data;
+
+    /// Importing the function to
+    /// render Markdown code.
+    use super::render_markdown;
+
+    /// Importing the function to
+    /// build options for rendering
+    /// Markdown code.
+    use super::build_markdown_options;
+
+    /// Importing the structure
+    /// holding the options for
+    /// rendering Markdown.
+    use crate::modules::units::MarkdownOptions;
+
+    /// Importing the structure
+    /// holding a parsed content
+    /// file.
+    use crate::modules::units::ContentStore;
+
+    #[test]
+    fn counts_the_lines_before_the_content() {
+        let src: String = String::from("---\nlayout: page\ntitle: Hi\n---\n# Hi");
+        let (front_matter, content, line_offset, content_offset) = split_front_matter(&src).unwrap();
+        assert_eq!(front_matter, "layout: page\ntitle: Hi");
+        assert_eq!(content, "# Hi");
+        assert_eq!(line_offset, 1);
+        assert_eq!(content_offset, 4);
+    }
+
+    #[test]
+    fn counts_the_lines_around_empty_front_matter() {
+        let src: String = String::from("\n---\n---\n# Hi");
+        let (front_matter, content, line_offset, content_offset) = split_front_matter(&src).unwrap();
+        assert_eq!(front_matter, "");
+        assert_eq!(content, "# Hi");
+        assert_eq!(line_offset, 2);
+        assert_eq!(content_offset, 3);
+    }
+
+    #[test]
+    fn needs_closed_front_matter() {
+        assert!(split_front_matter(&String::from("# Hi")).is_none());
+        assert!(split_front_matter(&String::from("---\nlayout: page\n# Hi")).is_none());
+    }
+
+    #[test]
+    fn types_csv_fields() {
+        assert_eq!(csv_field_type("42"), CsvColumnType::Integer);
+        assert_eq!(csv_field_type("-3"), CsvColumnType::Integer);
+        assert_eq!(csv_field_type("0"), CsvColumnType::Integer);
+        assert_eq!(csv_field_type("1.50"), CsvColumnType::Float);
+        assert_eq!(csv_field_type("0.5"), CsvColumnType::Float);
+        assert_eq!(csv_field_type("true"), CsvColumnType::Boolean);
+        assert_eq!(csv_field_type("007"), CsvColumnType::Text);
+        assert_eq!(csv_field_type("1e3"), CsvColumnType::Text);
+        assert_eq!(csv_field_type("1."), CsvColumnType::Text);
+        assert_eq!(csv_field_type("True"), CsvColumnType::Text);
+        assert_eq!(csv_field_type(""), CsvColumnType::Text);
+    }
+
+    #[test]
+    fn gives_every_csv_column_a_single_type() {
+        let src: String = String::from("price,count,code,active,note\n9.99,3,007,true,1\n9.90,4,123,false,abc\n10,5,042,true,2");
+        let rows: Vec<Value> = match parse_csv_data(&src).unwrap() {
+            Value::Array(rows) => rows,
+            _ => panic!("CSV data should parse into an array.")
+        };
+        let column = |name: &str| -> Vec<Value> {
+            rows.iter().map(|row| match row {
+                Value::Object(row) => row.get(name).unwrap().clone(),
+                _ => panic!("CSV rows should parse into objects.")
+            }).collect()
+        };
+        assert_eq!(column("price"), vec![Value::scalar(9.99f64), Value::scalar(9.9f64), Value::scalar(10f64)]);
+        assert_eq!(column("count"), vec![Value::scalar(3i64), Value::scalar(4i64), Value::scalar(5i64)]);
+        assert_eq!(column("code"), vec![Value::scalar("007".to_string()), Value::scalar("123".to_string()), Value::scalar("042".to_string())]);
+        assert_eq!(column("active"), vec![Value::scalar(true), Value::scalar(false), Value::scalar(true)]);
+        assert_eq!(column("note"), vec![Value::scalar("1".to_string()), Value::scalar("abc".to_string()), Value::scalar("2".to_string())]);
+    }
+
+    #[test]
+    fn turns_csv_rows_into_maps() {
+        let src: String = String::from("name,age\nAda,36\nBob,41");
+        let rows: Vec<Value> = match parse_csv_data(&src).unwrap() {
+            Value::Array(rows) => rows,
+            _ => panic!("CSV data should parse into an array.")
+        };
+        assert_eq!(rows.len(), 2);
+        match &rows[0] {
+            Value::Object(row) => {
+                assert_eq!(row.get("name"), Some(&Value::scalar("Ada".to_string())));
+                assert_eq!(row.get("age"), Some(&Value::scalar(36i64)));
+            },
+            _ => panic!("CSV rows should parse into objects.")
+        };
+        match &rows[1] {
+            Value::Object(row) => assert_eq!(row.get("age"), Some(&Value::scalar(41i64))),
+            _ => panic!("CSV rows should parse into objects.")
+        };
+    }
+
+    #[test]
+    fn keeps_structured_values_in_front_matter() {
+        let src: String = String::from("---\nlayout: post\ntitle: Hi\ntags: [a, b]\ndraft: false\nrating: 4\nauthor:\n  name: Ada\n---\nBody");
+        let document: ContentStore = parse_document(&src, &MarkdownOptions::default()).unwrap();
+        assert_eq!(document.layout, "post");
+        assert_eq!(document.params.get("title"), Some(&Value::scalar("Hi".to_string())));
+        assert_eq!(document.params.get("tags"), Some(&Value::Array(vec![Value::scalar("a".to_string()), Value::scalar("b".to_string())])));
+        let mut params: Object = Object::new();
+        for (key, value) in document.params {
+            params.insert(key.into(), value);
+        }
+        let mut globals: Object = Object::new();
+        globals.insert("params".into(), Value::Object(params));
+        let template: String = String::from("{{ params.title }}:{% for tag in params.tags %}{{ tag }},{% endfor %}{% if params.draft == false %}live{% endif %}:{{ params.rating | plus: 1 }}:{{ params.author.name }}");
+        let parsed: Template = ParserBuilder::with_stdlib().build().unwrap().parse(&template).unwrap();
+        assert_eq!(parsed.render(&globals).unwrap(), "Hi:a,b,live:5:Ada");
+    }
+
+    #[test]
+    fn keeps_data_files_as_they_are() {
+        let yml: String = String::from("nav:\n  - title: Home\n    children:\n      - title: About\nprice: 9.5\nstock: 3\nopen: true");
+        let json: String = String::from("{\"nav\": [{\"title\": \"Home\", \"children\": [{\"title\": \"About\"}]}], \"price\": 9.5, \"stock\": 3, \"open\": true}");
+        let from_yml: Value = parse_yml_data(&yml).unwrap();
+        assert_eq!(from_yml, parse_json_data(&json).unwrap());
+        let globals: Object = match from_yml {
+            Value::Object(globals) => globals,
+            _ => panic!("The data file should parse into an object.")
+        };
+        let template: String = String::from("{% for item in nav %}{{ item.title }}>{{ item.children[0].title }}{% endfor %}:{{ price | times: 2 }}:{{ stock | plus: 1 }}:{{ open }}");
+        let parsed: Template = ParserBuilder::with_stdlib().build().unwrap().parse(&template).unwrap();
+        assert_eq!(parsed.render(&globals).unwrap(), "Home>About:19:4:true");
+        assert_eq!(parse_yml_data(&String::from("- 1\n- two")).unwrap(), Value::Array(vec![Value::scalar(1i64), Value::scalar("two".to_string())]));
+        assert_eq!(parse_json_data(&String::from("\"plain\"")).unwrap(), Value::scalar("plain".to_string()));
+    }
+
+    /// Renders the supplied Markdown
+    /// code with the supplied options.
+    fn render(src: &str, options: &MarkdownOptions) -> String {
+        render_markdown(src, &build_markdown_options(options), &0).unwrap()
+    }
+
+    #[test]
+    fn turns_markdown_constructs_on_and_off() {
+        let table: &str = "| a |\n| - |\n| b |";
+        let plain: MarkdownOptions = MarkdownOptions::default();
+        assert!(!render(table, &plain).contains("<table>"));
+        assert!(!render("~~gone~~", &plain).contains("<del>"));
+        let gfm: MarkdownOptions = MarkdownOptions { gfm: true, ..MarkdownOptions::default() };
+        assert!(render(table, &gfm).contains("<table>"));
+        assert!(render("~~gone~~", &gfm).contains("<del>gone</del>"));
+        assert!(render("- [x] done", &gfm).contains("type=\"checkbox\""));
+        let no_tables: MarkdownOptions = MarkdownOptions { gfm: true, tables: Some(false), ..MarkdownOptions::default() };
+        assert!(!render(table, &no_tables).contains("<table>"));
+        assert!(render("~~gone~~", &no_tables).contains("<del>"));
+        let strikethrough: MarkdownOptions = MarkdownOptions { strikethrough: Some(true), ..MarkdownOptions::default() };
+        assert!(render("~~gone~~", &strikethrough).contains("<del>"));
+        assert!(!render(table, &strikethrough).contains("<table>"));
+    }
+
+    #[test]
+    fn escapes_raw_html_unless_allowed() {
+        let src: &str = "A <span class=\"x\">span</span>.";
+        assert!(render(src, &MarkdownOptions::default()).contains("&lt;span"));
+        let raw: MarkdownOptions = MarkdownOptions { raw_html: true, ..MarkdownOptions::default() };
+        assert!(render(src, &raw).contains("<span class=\"x\">span</span>"));
+        let math: MarkdownOptions = MarkdownOptions { math: true, ..MarkdownOptions::default() };
+        assert!(render("$x$", &math).contains("math"));
+        assert!(!render("$x$", &MarkdownOptions::default()).contains("math"));
+    }
 }
\ No newline at end of file
@@ -16,6 +16,11 @@ use std::path::PathBuf;
 /// structure to handle errors.
 use super::err::MandyErr;
 
+/// Importing the structure that
+/// holds what is known about
+/// an error.
+use super::err::ErrInfo;
+
 /// Importing the structure that holds all
 /// information on a configuration file in a
 /// a Mandy project.
@@ -25,19 +30,41 @@ use super::units::ConfigFile;
 /// file of a Mandy project.
 use super::gather::read_config;
 
+/// Importing the function to remove
+/// the build cache of a Mandy project.
+use super::cache::remove_build_cache;
+
+/// Importing the function to get the name
+/// of the staging directory.
+use super::compile::get_staging_dir;
+
+/// Importing the function to get the name
+/// of the directory holding the last build
+/// during a swap.
+use super::compile::get_previous_dir;
+
 /// Cleans a Mandy project of any directory containing the compiled
-/// static site. If this operation fails, an error is returned.
+/// static site, of any directories left behind by an interrupted build,
+/// and of the build cache. If this operation fails, an error is returned.
 pub fn clean_project(dir: &String) -> Result<(), MandyErr>{
-    let config_file: ConfigFile = match read_config(dir){
-        Ok(config_file) => config_file,
-        Err(e) => return Err::<(), MandyErr>(MandyErr::new(&e.to_string()))
-    };
+    let config_file: ConfigFile = read_config(dir)?;
     let mut dist_buf: PathBuf = PathBuf::new();
     dist_buf.push(dir);
-    dist_buf.push(config_file.contents.dist_dir);
-    let del_op: () = match coutils::del_dir(&dist_buf.display().to_string()){
-        Ok(del_op) => del_op,
-        Err(e) => return Err::<(), MandyErr>(MandyErr::new(&e.to_string()))
+    dist_buf.push(&config_file.contents.dist_dir);
+    let _del_op: () = match coutils::del_dir(&dist_buf.display().to_string()){
+        Ok(_del_op) => _del_op,
+        Err(e) => return Err::<(), MandyErr>(MandyErr::Io(Box::new(ErrInfo::new(&e.to_string()))).with_path(&dist_buf))
     };
-    Ok(del_op)
+    for leftover_dir in [get_staging_dir(&config_file.contents.dist_dir), get_previous_dir(&config_file.contents.dist_dir)] {
+        let mut leftover_buf: PathBuf = PathBuf::new();
+        leftover_buf.push(dir);
+        leftover_buf.push(leftover_dir);
+        if leftover_buf.exists(){
+            let _del_op: () = match coutils::del_dir(&leftover_buf.display().to_string()){
+                Ok(_del_op) => _del_op,
+                Err(e) => return Err::<(), MandyErr>(MandyErr::Io(Box::new(ErrInfo::new(&e.to_string()))).with_path(&leftover_buf))
+            };
+        }
+    }
+    remove_build_cache(dir)
 }
\ No newline at end of file
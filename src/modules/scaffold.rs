@@ -0,0 +1,291 @@
+/*
+Mandy by Alyx Shang.
+Licensed under the FSL v1.
+*/
+
+/// Importing Rust's standard
+/// API for working with the
+/// file system.
+use std::fs;
+
+/// Importing the "PathBuf"
+/// structure from Rust's standard
+/// library.
+use std::path::PathBuf;
+
+/// Importing Mandy's error-handling
+/// structure to handle errors.
+use super::err::MandyErr;
+
+/// Importing the structure that
+/// holds what is known about
+/// an error.
+use super::err::ErrInfo;
+
+/// Importing the enum to
+/// describe the format of the
+/// configuration file.
+use super::units::UserDataFileType;
+
+/// Importing the function to report
+/// a written file.
+use super::logger::log_written;
+
+/// The configuration file of a new
+/// Mandy project in YAML format.
+pub const STARTER_YML_CONFIG: &str = "title: My Mandy site
+description: A static site built with Mandy.
+tl_domain: https://example.com
+prod_url: https://example.com
+dev_url: http://localhost:8080
+dist_dir: dist
+seo: true
+copy_files: false
+copy_entities: []
+has_loop_content: true
+loop_content_dirs:
+  - posts
+user_config:
+  author: Your name
+";
+
+/// The configuration file of a new
+/// Mandy project in JSON format.
+pub const STARTER_JSON_CONFIG: &str = "{
+  \"title\": \"My Mandy site\",
+  \"description\": \"A static site built with Mandy.\",
+  \"tl_domain\": \"https://example.com\",
+  \"prod_url\": \"https://example.com\",
+  \"dev_url\": \"http://localhost:8080\",
+  \"dist_dir\": \"dist\",
+  \"seo\": true,
+  \"copy_files\": false,
+  \"copy_entities\": [],
+  \"has_loop_content\": true,
+  \"loop_content_dirs\": [\"posts\"],
+  \"user_config\": {
+    \"author\": \"Your name\"
+  }
+}
+";
+
+/// The configuration file of a new
+/// Mandy project in TOML format.
+pub const STARTER_TOML_CONFIG: &str = "title = \"My Mandy site\"
+description = \"A static site built with Mandy.\"
+tl_domain = \"https://example.com\"
+prod_url = \"https://example.com\"
+dev_url = \"http://localhost:8080\"
+dist_dir = \"dist\"
+seo = true
+copy_files = false
+copy_entities = []
+has_loop_content = true
+loop_content_dirs = [\"posts\"]
+
+[user_config]
+author = \"Your name\"
+";
+
+/// The default layout of a
+/// new Mandy project.
+pub const STARTER_LAYOUT: &str = "<!DOCTYPE html>
+<html lang=\"en\">
+  {% include \"head\" %}
+  <body>
+    <main>
+      <h1>{{ page.params.title }}</h1>
+      {{ page.content }}
+      {% if page.params.listing %}
+      <ul>
+        {% for post in loop_content[page.params.listing] %}
+        <li><a href=\"{{ baseurl }}{{ post.url }}\">{{ post.params.title }}</a></li>
+        {% endfor %}
+      </ul>
+      {% endif %}
+    </main>
+    {% include \"footer\" %}
+  </body>
+</html>
+";
+
+/// The partial template for the
+/// head of every page in a new
+/// Mandy project.
+pub const STARTER_HEAD: &str = "<head>
+  <meta charset=\"utf-8\"/>
+  <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\"/>
+  <meta name=\"description\" content=\"{{ site.description }}\"/>
+  <title>{{ page.params.title }} | {{ site.title }}</title>
+  <link rel=\"stylesheet\" href=\"{{ baseurl }}/css/index.css\"/>
+</head>
+";
+
+/// The partial template for the
+/// footer of every page in a new
+/// Mandy project.
+pub const STARTER_FOOTER: &str = "<footer>
+  <p>{{ site.title }} by {{ site.user_config.author }}</p>
+</footer>
+";
+
+/// The SASS file of a new
+/// Mandy project.
+pub const STARTER_SASS: &str = "$text: #222222;
+$accent: #3b5bdb;
+
+body {
+  color: $text;
+  font-family: sans-serif;
+  margin: 0 auto;
+  max-width: 40rem;
+  padding: 1rem;
+}
+
+a {
+  color: $accent;
+}
+";
+
+/// The home page of a new
+/// Mandy project. It lists
+/// all posts.
+pub const STARTER_INDEX: &str = "---
+layout: default
+title: Home
+listing: posts
+---
+Welcome to your new Mandy site! Edit `index.markdown` to change this page.
+
+## Posts
+";
+
+/// The example post of a new
+/// Mandy project.
+pub const STARTER_POST: &str = "---
+layout: default
+title: Hello, world!
+---
+This is your first post. Every file in the `posts` directory is listed on the home page.
+";
+
+/// The archetype for new posts
+/// in a new Mandy project.
+pub const STARTER_POST_ARCHETYPE: &str = "---
+layout: {{ layout }}
+title: \"{{ yaml_title }}\"
+date: \"{{ date }}\"
+---
+Write your post here.
+";
+
+/// The list of files Git should
+/// ignore in a new Mandy project.
+pub const STARTER_GITIGNORE: &str = "dist
+.dist-staging
+.dist-previous
+.mandy-cache
+";
+
+/// Creates a new Mandy project in the supplied directory. The project has a
+/// configuration file in the supplied format, a default layout, partial templates
+/// for the head and the footer of every page, a SASS file, a home page, and an
+/// example post in the "posts" loop content directory with an archetype for
+/// new posts. The project can be compiled right away. The directory is created
+/// if it does not exist and must be empty if it does. Returns a "Result" type with an empty closure.
+pub fn init_project(dir: &String, config_type: &UserDataFileType) -> Result<(), MandyErr>{
+    let project_buf: PathBuf = PathBuf::from(dir);
+    if project_buf.exists(){
+        let mut entries: fs::ReadDir = match fs::read_dir(&project_buf){
+            Ok(entries) => entries,
+            Err(e) => return Err::<(), MandyErr>(MandyErr::Io(Box::new(ErrInfo::from_source(e))).with_path(&project_buf))
+        };
+        if entries.next().is_some(){
+            let e: String = format!("The directory \"{}\" is not empty. A new Mandy project can only be created in an empty directory.", dir);
+            return Err::<(), MandyErr>(MandyErr::Usage(Box::new(ErrInfo::new(&e.to_string()))));
+        }
+    }
+    let config_file: (&str, &str) = match config_type {
+        UserDataFileType::YAML => ("config.yml", STARTER_YML_CONFIG),
+        UserDataFileType::JSON => ("config.json", STARTER_JSON_CONFIG),
+        UserDataFileType::TOML => ("config.toml", STARTER_TOML_CONFIG),
+        UserDataFileType::CSV => {
+            let e: String = "The configuration file of a new project cannot be written in the CSV format.".to_string();
+            return Err::<(), MandyErr>(MandyErr::Usage(Box::new(ErrInfo::new(&e.to_string()))));
+        }
+    };
+    let files: Vec<(&str, &str)> = vec![
+        config_file,
+        ("layouts/default.liquid", STARTER_LAYOUT),
+        ("partials/head.liquid", STARTER_HEAD),
+        ("partials/footer.liquid", STARTER_FOOTER),
+        ("sass/index.scss", STARTER_SASS),
+        ("index.markdown", STARTER_INDEX),
+        ("posts/hello-world.markdown", STARTER_POST),
+        ("archetypes/posts.markdown", STARTER_POST_ARCHETYPE),
+        (".gitignore", STARTER_GITIGNORE)
+    ];
+    for (name, contents) in files {
+        let file_buf: PathBuf = project_buf.join(name);
+        if let Some(parent) = file_buf.parent() {
+            let _create_op: () = match fs::create_dir_all(parent){
+                Ok(_create_op) => _create_op,
+                Err(e) => return Err::<(), MandyErr>(MandyErr::Io(Box::new(ErrInfo::from_source(e))).with_path(parent))
+            };
+        }
+        let _write_op: () = match fs::write(&file_buf, contents){
+            Ok(_write_op) => _write_op,
+            Err(e) => return Err::<(), MandyErr>(MandyErr::Io(Box::new(ErrInfo::from_source(e))).with_path(&file_buf))
+        };
+        log_written(&file_buf, &None);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+
+    /// Importing the structure
+    /// for directories created
+    /// for tests.
+    use crate::modules::testing::TempDir;
+
+    /// Importing the enum to
+    /// describe the format of the
+    /// configuration file.
+    use crate::modules::units::UserDataFileType;
+
+    /// Importing the function
+    /// to compile a project.
+    use crate::modules::compile::compile_project_with_baseurl;
+
+    /// Importing the function
+    /// to create a new project.
+    use super::init_project;
+
+    #[test]
+    fn creates_projects_that_compile_right_away() {
+        for (name, config_type, config_file) in [
+            ("yaml", UserDataFileType::YAML, "config.yml"),
+            ("json", UserDataFileType::JSON, "config.json"),
+            ("toml", UserDataFileType::TOML, "config.toml")
+        ] {
+            let project: TempDir = TempDir::new(&format!("init-{}", name));
+            let dir: String = project.path.join("site").display().to_string();
+            init_project(&dir, &config_type).unwrap();
+            assert!(project.path.join("site").join(config_file).exists());
+            compile_project_with_baseurl(&dir, &String::new()).unwrap();
+            assert!(project.path.join("site/dist/index.html").exists(), "{}", name);
+            assert!(project.path.join("site/dist/posts/hello-world/index.html").exists(), "{}", name);
+        }
+    }
+
+    #[test]
+    fn refuses_non_empty_directories_and_csv_configuration() {
+        let project: TempDir = TempDir::new("init-refused");
+        assert!(init_project(&project.dir(), &UserDataFileType::CSV).is_err());
+        project.write("notes.txt", "Keep me.");
+        assert!(init_project(&project.dir(), &UserDataFileType::YAML).is_err());
+        assert!(!project.path.join("config.yml").exists());
+    }
+}
\ No newline at end of file
@@ -0,0 +1,541 @@
+/*
+Mandy by Alyx Shang.
+Licensed under the FSL v1.
+*/
+
+/// Importing the standard
+/// library for working with
+/// the filesystem.
+use std::fs;
+
+/// Importing the "thread"
+/// module to handle requests
+/// concurrently.
+use std::thread;
+
+/// Importing the "Read"
+/// trait to read from streams.
+use std::io::Read;
+
+/// Importing the "Write"
+/// trait to write to streams.
+use std::io::Write;
+
+/// Importing the "PathBuf"
+/// structure from Rust's standard
+/// library.
+use std::path::PathBuf;
+
+/// Importing the "Path"
+/// structure from Rust's standard
+/// library.
+use std::path::Path;
+
+/// Importing the "TcpStream"
+/// structure to respond to
+/// requests.
+use std::net::TcpStream;
+
+/// Importing the "Duration"
+/// structure to keep live-reload
+/// connections open.
+use std::time::Duration;
+
+/// Importing the "Arc" structure
+/// to share data between threads.
+use std::sync::Arc;
+
+/// Importing the "Mutex" structure
+/// to share data between threads.
+use std::sync::Mutex;
+
+/// Importing the "channel"
+/// function to send messages
+/// between threads.
+use std::sync::mpsc::channel;
+
+/// Importing the "Sender" structure
+/// to send messages between threads.
+use std::sync::mpsc::Sender;
+
+/// Importing the "Receiver" structure
+/// to receive messages between threads.
+use std::sync::mpsc::Receiver;
+
+/// Importing Mandy's error-handling
+/// structure to handle errors.
+use super::err::MandyErr;
+
+/// Importing the structure that
+/// holds what is known about
+/// an error.
+use super::err::ErrInfo;
+
+/// Importing the "TcpListener"
+/// structure to listen for
+/// requests.
+use std::net::TcpListener;
+
+/// Importing the structure that holds all
+/// information on a configuration file in a
+/// a Mandy project.
+use super::units::ConfigFile;
+
+/// Importing the function
+/// to read a Mandy project's
+/// configuration settings.
+use super::gather::read_config;
+
+/// Importing the structure to
+/// store a response to a request.
+use super::units::ServerResponse;
+
+/// Importing the enum that describes
+/// how a Mandy project was rebuilt.
+use super::units::RebuildKind;
+
+/// Importing the structure that holds
+/// all browsers waiting for live-reload
+/// messages.
+use super::units::ReloadClients;
+
+/// Importing the function to watch
+/// a Mandy project for changes.
+use super::watch::watch_for_changes;
+
+/// Importing the function to compile
+/// a Mandy project with a given base URL.
+use super::compile::compile_project_with_baseurl;
+
+/// Importing the function
+/// to report an error.
+use super::logger::log_error;
+
+/// Importing the function
+/// to report the address
+/// the server listens on.
+use super::logger::log_info;
+
+/// The port the development server listens on
+/// if the "dev_url" option does not contain one.
+pub const DEFAULT_PORT: u16 = 8080;
+
+/// The URL path browsers connect to 
+/// for receiving live-reload messages.
+pub const LIVE_RELOAD_PATH: &str = "/__mandy/livereload";
+
+/// The script injected into every HTML page
+/// served by the development server. It reloads
+/// the page after a rebuild and swaps the 
+/// stylesheet after a rebuild of SASS files.
+/// This script is never written to disk.
+pub const LIVE_RELOAD_SCRIPT: &str = r#"<script>
+(function(){
+  var source = new EventSource("/__mandy/livereload");
+  source.addEventListener("reload", function(){
+    window.location.reload();
+  });
+  source.addEventListener("css", function(){
+    var links = document.querySelectorAll("link[rel=stylesheet]");
+    for (var i = 0; i < links.length; i++){
+      var href = links[i].getAttribute("href");
+      if (href && href.indexOf("css/index.css") !== -1){
+        links[i].setAttribute("href", href.split("?")[0] + "?mandy=" + Date.now());
+      }
+    }
+  });
+})();
+</script>
+"#;
+
+/// Attempts to extract the port from the "dev_url" option of a Mandy project.
+/// If no port is found, the default port is returned.
+pub fn get_dev_port(dev_url: &str) -> u16 {
+    let without_scheme: &str = match dev_url.split_once("://"){
+        Some((_scheme, rest)) => rest,
+        None => dev_url
+    };
+    let host: &str = match without_scheme.split_once('/'){
+        Some((host, _path)) => host,
+        None => without_scheme
+    };
+    match host.rsplit_once(':'){
+        Some((_name, port)) => match port.parse::<u16>(){
+            Ok(port) => port,
+            Err(_e) => DEFAULT_PORT
+        },
+        None => DEFAULT_PORT
+    }
+}
+
+/// Returns the MIME type for the given file
+/// extension.
+pub fn get_content_type(path: &Path) -> String {
+    let ext: String = match path.extension(){
+        Some(ext) => ext.to_string_lossy().to_lowercase(),
+        None => String::from("")
+    };
+    let mime: &str = match ext.as_str(){
+        "html" | "htm" => "text/html; charset=utf-8",
+        "css" => "text/css; charset=utf-8",
+        "js" => "text/javascript; charset=utf-8",
+        "json" => "application/json",
+        "xml" => "application/xml",
+        "txt" => "text/plain; charset=utf-8",
+        "svg" => "image/svg+xml",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "ico" => "image/x-icon",
+        "woff" => "font/woff",
+        "woff2" => "font/woff2",
+        "ttf" => "font/ttf",
+        "pdf" => "application/pdf",
+        _ => "application/octet-stream"
+    };
+    mime.to_string()
+}
+
+/// Decodes percent-encoded characters
+/// in the path of a requested URL.
+pub fn decode_url_path(path: &str) -> String {
+    let bytes: &[u8] = path.as_bytes();
+    let mut decoded: Vec<u8> = Vec::new();
+    let mut idx: usize = 0;
+    while idx < bytes.len(){
+        if bytes[idx] == b'%' && idx + 2 < bytes.len() {
+            let hex: String = String::from_utf8_lossy(&bytes[idx+1..idx+3]).to_string();
+            match u8::from_str_radix(&hex, 16){
+                Ok(byte) => {
+                    decoded.push(byte);
+                    idx += 3;
+                },
+                Err(_e) => {
+                    decoded.push(bytes[idx]);
+                    idx += 1;
+                }
+            };
+        }
+        else {
+            decoded.push(bytes[idx]);
+            idx += 1;
+        }
+    }
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+/// Attempts to find the custom page for missing files
+/// in the directory containing the compiled Mandy project.
+/// A page at "404.html" or at "404/index.html" is accepted.
+pub fn find_not_found_page(dist_buf: &Path) -> Option<PathBuf> {
+    let mut flat_buf: PathBuf = dist_buf.to_path_buf();
+    flat_buf.push("404.html");
+    let mut nested_buf: PathBuf = dist_buf.to_path_buf();
+    nested_buf.push("404");
+    nested_buf.push("index.html");
+    if flat_buf.is_file(){
+        Some(flat_buf)
+    }
+    else if nested_buf.is_file(){
+        Some(nested_buf)
+    }
+    else {
+        None
+    }
+}
+
+/// Resolves the path of a request to a response from the
+/// directory containing the compiled Mandy project. Directory
+/// URLs are answered with the "index.html" file inside them.
+pub fn resolve_request(dist_buf: &Path, url_path: &str) -> ServerResponse {
+    let clean_path: &str = match url_path.split_once(['?', '#']){
+        Some((path, _rest)) => path,
+        None => url_path
+    };
+    let decoded: String = decode_url_path(clean_path);
+    let mut file_buf: PathBuf = dist_buf.to_path_buf();
+    let mut is_safe: bool = true;
+    for segment in decoded.split('/'){
+        if segment.is_empty() || segment == "."{}
+        else if segment == ".." || segment.contains('\\'){
+            is_safe = false;
+        }
+        else {
+            file_buf.push(segment);
+        }
+    }
+    if is_safe && file_buf.is_dir(){
+        if !decoded.ends_with('/'){
+            return ServerResponse{
+                status: String::from("301 Moved Permanently"),
+                content_type: String::from("text/plain; charset=utf-8"),
+                location: Some(format!("{}/", clean_path)),
+                body: Vec::new()
+            };
+        }
+        file_buf.push("index.html");
+    }
+    if is_safe && file_buf.is_file(){
+        match fs::read(&file_buf){
+            Ok(body) => {
+                return ServerResponse{
+                    status: String::from("200 OK"),
+                    content_type: get_content_type(&file_buf),
+                    location: None,
+                    body
+                };
+            },
+            Err(_e) => {}
+        };
+    }
+    let body: Vec<u8> = match find_not_found_page(dist_buf){
+        Some(not_found_buf) => match fs::read(&not_found_buf){
+            Ok(body) => body,
+            Err(_e) => b"404 Not Found".to_vec()
+        },
+        None => b"404 Not Found".to_vec()
+    };
+    ServerResponse{
+        status: String::from("404 Not Found"),
+        content_type: String::from("text/html; charset=utf-8"),
+        location: None,
+        body
+    }
+}
+
+/// Injects the live-reload script into the supplied HTML code.
+/// The script is placed before the closing "body" tag or at
+/// the end of the document if there is no such tag.
+pub fn inject_live_reload(html: &[u8]) -> Vec<u8> {
+    let source: String = String::from_utf8_lossy(html).to_string();
+    let lowered: String = source.to_lowercase();
+    match lowered.rfind("</body>"){
+        Some(idx) => format!("{}{}{}", &source[..idx], LIVE_RELOAD_SCRIPT, &source[idx..]).into_bytes(),
+        None => format!("{}{}", source, LIVE_RELOAD_SCRIPT).into_bytes()
+    }
+}
+
+/// Sends a live-reload message to all connected browsers after
+/// a rebuild. Browsers are told to swap the stylesheet if only SASS
+/// files were rebuilt and to reload the page otherwise. Browsers
+/// that have disconnected are removed.
+pub fn broadcast_rebuild(clients: &ReloadClients, kind: &RebuildKind) {
+    let message: String = match kind {
+        RebuildKind::Sass => String::from("css"),
+        _ => String::from("reload")
+    };
+    match clients.senders.lock(){
+        Ok(mut senders) => senders.retain(|sender| sender.send(message.clone()).is_ok()),
+        Err(e) => log_error(&MandyErr::Other(Box::new(ErrInfo::new(&e.to_string()))))
+    };
+}
+
+/// Keeps a connection from a browser open and forwards live-reload
+/// messages to it as server-sent events. Returns once the browser
+/// has disconnected.
+pub fn stream_reload_events(stream: &mut TcpStream, clients: &ReloadClients) -> Result<(), MandyErr> {
+    let (sender, receiver): (Sender<String>, Receiver<String>) = channel::<String>();
+    match clients.senders.lock(){
+        Ok(mut senders) => senders.push(sender),
+        Err(e) => return Err::<(), MandyErr>(MandyErr::Other(Box::new(ErrInfo::new(&e.to_string()))))
+    };
+    let head: &str = "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\nConnection: keep-alive\r\n\r\n";
+    let _head_op: () = match stream.write_all(head.as_bytes()){
+        Ok(_head_op) => _head_op,
+        Err(e) => return Err::<(), MandyErr>(MandyErr::Io(Box::new(ErrInfo::from_source(e))))
+    };
+    loop {
+        let event: String = match receiver.recv_timeout(Duration::from_secs(15)){
+            Ok(message) => format!("event: {}\ndata: {}\n\n", message, message),
+            Err(_e) => String::from(": keep-alive\n\n")
+        };
+        let sent: bool = stream.write_all(event.as_bytes()).is_ok() && stream.flush().is_ok();
+        if !sent {
+            return Ok(());
+        }
+    }
+}
+
+/// Writes an instance of the "ServerResponse"
+/// structure to the given stream. Returns an error if
+/// this fails.
+pub fn write_response(stream: &mut TcpStream, response: &ServerResponse, head_only: &bool) -> Result<(), MandyErr> {
+    let mut head: String = format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nCache-Control: no-cache\r\nConnection: close\r\n",
+        &response.status,
+        &response.content_type,
+        response.body.len()
+    );
+    if let Some(location) = &response.location {
+        head.push_str(&format!("Location: {}\r\n", location));
+    }
+    head.push_str("\r\n");
+    let _head_op: () = match stream.write_all(head.as_bytes()){
+        Ok(_head_op) => _head_op,
+        Err(e) => return Err::<(), MandyErr>(MandyErr::Io(Box::new(ErrInfo::from_source(e))))
+    };
+    if !*head_only {
+        let _body_op: () = match stream.write_all(&response.body){
+            Ok(_body_op) => _body_op,
+            Err(e) => return Err::<(), MandyErr>(MandyErr::Io(Box::new(ErrInfo::from_source(e))))
+        };
+    }
+    match stream.flush(){
+        Ok(_flush_op) => Ok(()),
+        Err(e) => Err::<(), MandyErr>(MandyErr::Io(Box::new(ErrInfo::from_source(e))))
+    }
+}
+
+/// Handles a single request to the development
+/// server. Requests for HTML pages are answered with
+/// the live-reload script injected. Returns an error if this fails.
+pub fn handle_connection(
+    mut stream: TcpStream,
+    dist_buf: &Path,
+    clients: &ReloadClients
+) -> Result<(), MandyErr> {
+    let mut buffer: [u8; 8192] = [0; 8192];
+    let read_len: usize = match stream.read(&mut buffer){
+        Ok(read_len) => read_len,
+        Err(e) => return Err::<(), MandyErr>(MandyErr::Io(Box::new(ErrInfo::from_source(e))))
+    };
+    let request: String = String::from_utf8_lossy(&buffer[..read_len]).to_string();
+    let request_line: Vec<&str> = match request.lines().next(){
+        Some(line) => line.split_whitespace().collect(),
+        None => Vec::new()
+    };
+    if request_line.len() < 2 {
+        let response: ServerResponse = ServerResponse{
+            status: String::from("400 Bad Request"),
+            content_type: String::from("text/plain; charset=utf-8"),
+            location: None,
+            body: b"400 Bad Request".to_vec()
+        };
+        return write_response(&mut stream, &response, &false);
+    }
+    let method: &str = request_line[0];
+    if method != "GET" && method != "HEAD" {
+        let response: ServerResponse = ServerResponse{
+            status: String::from("405 Method Not Allowed"),
+            content_type: String::from("text/plain; charset=utf-8"),
+            location: None,
+            body: b"405 Method Not Allowed".to_vec()
+        };
+        return write_response(&mut stream, &response, &false);
+    }
+    if request_line[1] == LIVE_RELOAD_PATH {
+        return stream_reload_events(&mut stream, clients);
+    }
+    let mut response: ServerResponse = resolve_request(dist_buf, request_line[1]);
+    if response.content_type.starts_with("text/html"){
+        response.body = inject_live_reload(&response.body);
+    }
+    write_response(&mut stream, &response, &(method == "HEAD"))
+}
+
+/// Compiles the Mandy project in the supplied directory with the "dev_url"
+/// option as the base URL and serves the compiled project over HTTP on
+/// the local machine. The project is recompiled on changes and connected
+/// browsers are reloaded. This function only returns if an error occurs.
+pub fn serve_project(dir: &String) -> Result<(), MandyErr> {
+    let config: ConfigFile = read_config(dir)?;
+    let mut dist_buf: PathBuf = PathBuf::new();
+    dist_buf.push(dir);
+    dist_buf.push(&config.contents.dist_dir);
+    let _compile_op: () = compile_project_with_baseurl(dir, &config.contents.dev_url)?;
+    let port: u16 = get_dev_port(&config.contents.dev_url);
+    let listener: TcpListener = match TcpListener::bind(("127.0.0.1", port)){
+        Ok(listener) => listener,
+        Err(e) => return Err::<(), MandyErr>(MandyErr::Io(Box::new(ErrInfo::from_source(e))))
+    };
+    let clients: ReloadClients = ReloadClients{ senders: Arc::new(Mutex::new(Vec::new())) };
+    let watch_clients: ReloadClients = clients.clone();
+    let watch_dir: String = dir.clone();
+    let watch_baseurl: String = config.contents.dev_url.clone();
+    thread::spawn(move || {
+        let on_rebuild = |kind: &RebuildKind| broadcast_rebuild(&watch_clients, kind);
+        match watch_for_changes(&watch_dir, &watch_baseurl, &on_rebuild){
+            Ok(_watch_op) => {},
+            Err(e) => log_error(&e)
+        };
+    });
+    log_info(&format!("Serving the Mandy project at \"{}\" on http://127.0.0.1:{}/.", dir, port));
+    for stream in listener.incoming(){
+        let stream: TcpStream = match stream {
+            Ok(stream) => stream,
+            Err(e) => {
+                log_error(&MandyErr::Io(Box::new(ErrInfo::from_source(e))));
+                continue;
+            }
+        };
+        let thread_dist_buf: PathBuf = dist_buf.clone();
+        let thread_clients: ReloadClients = clients.clone();
+        thread::spawn(move || {
+            match handle_connection(stream, &thread_dist_buf, &thread_clients){
+                Ok(_handled) => {},
+                Err(e) => log_error(&e)
+            };
+        });
+    }
+    Ok(())
+}
+
+/// Tests for resolving requests
+/// to files of the compiled project.
+#[cfg(test)]
+mod tests {
+
+    /// Importing the "PathBuf"
+    /// structure from Rust's standard
+    /// library.
+    use std::path::PathBuf;
+
+    /// Importing the structure holding
+    /// a directory created for a test.
+    use crate::modules::testing::TempDir;
+
+    /// Importing the function
+    /// under test.
+    use super::resolve_request;
+
+    /// Creates a project directory with a compiled project
+    /// holding a single page and a secret file next to it.
+    /// Returns the project directory and the directory for
+    /// the compiled project.
+    fn setup(name: &str) -> (TempDir, PathBuf) {
+        let project: TempDir = TempDir::new(name);
+        project.write("dist/page/index.html", "page");
+        project.write("secret.txt", "secret");
+        let dist_buf: PathBuf = project.path.join("dist");
+        (project, dist_buf)
+    }
+
+    #[test]
+    fn serves_pages_inside_the_compiled_project() {
+        let (_project, dist_buf) = setup("serve-pages");
+        let response = resolve_request(&dist_buf, &String::from("/page/"));
+        assert_eq!(response.status, "200 OK");
+        assert_eq!(response.body, b"page".to_vec());
+        let redirect = resolve_request(&dist_buf, &String::from("/page?x=1"));
+        assert_eq!(redirect.status, "301 Moved Permanently");
+        assert_eq!(redirect.location, Some(String::from("/page/")));
+    }
+
+    #[test]
+    fn refuses_paths_leaving_the_compiled_project() {
+        let (_project, dist_buf) = setup("serve-traversal");
+        let requests: Vec<&str> = vec![
+            "/../secret.txt",
+            "/page/../../secret.txt",
+            "/%2e%2e/secret.txt",
+            "/%2E%2E%2Fsecret.txt",
+            "/..%5Csecret.txt"
+        ];
+        for request in requests {
+            let response = resolve_request(&dist_buf, &String::from(request));
+            assert_eq!(response.status, "404 Not Found", "{}", request);
+            assert_ne!(response.body, b"secret".to_vec(), "{}", request);
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,82 @@
+/*
+Mandy by Alyx Shang.
+Licensed under the FSL v1.
+*/
+
+/// Importing Rust's standard
+/// API for working with the
+/// file system.
+use std::fs;
+
+/// Importing the "PathBuf"
+/// structure from Rust's standard
+/// library.
+use std::path::PathBuf;
+
+/// Importing the "AtomicUsize"
+/// structure to count the directories
+/// created for tests.
+use std::sync::atomic::AtomicUsize;
+
+/// Importing the "Ordering"
+/// enum to update the count of
+/// directories created for tests.
+use std::sync::atomic::Ordering;
+
+/// The number of directories created
+/// for tests so far, so that every
+/// directory has its own name.
+static TEMP_DIRS: AtomicUsize = AtomicUsize::new(0);
+
+/// A structure to hold an empty
+/// directory created for a test.
+/// The directory and everything in
+/// it is removed once the structure
+/// is dropped, even if the test fails.
+pub struct TempDir {
+    pub path: PathBuf
+}
+
+/// Implementing functions
+/// for the "TempDir" structure.
+impl TempDir {
+
+    /// Creates an empty directory named after
+    /// the supplied name inside the temporary
+    /// directory of the system.
+    pub fn new(name: &str) -> TempDir {
+        let count: usize = TEMP_DIRS.fetch_add(1, Ordering::SeqCst);
+        let mut path: PathBuf = std::env::temp_dir();
+        path.push(format!("mandy-{}-{}-{}", name, std::process::id(), count));
+        let _del_op = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).unwrap();
+        TempDir { path }
+    }
+
+    /// Returns the path of the directory
+    /// as a string, as Mandy takes the
+    /// directories of projects.
+    pub fn dir(&self) -> String {
+        self.path.display().to_string()
+    }
+
+    /// Writes the supplied contents to the file at the
+    /// supplied path inside the directory, creating its
+    /// parent directories. Returns the path of the file.
+    pub fn write(&self, relative: &str, contents: &str) -> PathBuf {
+        let file_buf: PathBuf = self.path.join(relative);
+        if let Some(parent) = file_buf.parent(){
+            fs::create_dir_all(parent).unwrap();
+        }
+        fs::write(&file_buf, contents).unwrap();
+        file_buf
+    }
+}
+
+/// Removes the directory and
+/// everything in it.
+impl Drop for TempDir {
+    fn drop(&mut self){
+        let _del_op = fs::remove_dir_all(&self.path);
+    }
+}
\ No newline at end of file
@@ -15,6 +15,12 @@ use liquid::ValueView;
 /// structures in this module.
 use serde::Deserialize;
 
+/// Importing the
+/// "Serialize" trait
+/// to save the build
+/// cache to disk.
+use serde::Serialize;
+
 /// Importing Rust's standard
 /// API for working with paths.
 use std::path::PathBuf;
@@ -25,42 +31,125 @@ use std::path::PathBuf;
 /// rendering Liquid templates.
 use liquid::ObjectView;
 
+/// Importing the "Template"
+/// structure to store parsed
+/// layouts.
+use liquid::Template;
+
+/// Importing the "Value"
+/// enum to hold structured
+/// values from configuration
+/// files and front matter.
+use liquid::model::Value;
+
 /// Importing Rust's standard API
 /// for working with maps.
 use std::collections::HashMap;
 
-/// An enum that describes the two formats
-/// of data file accepted by Mandy: JSON and YAML.
+/// Importing the "Arc" structure
+/// to share data between threads.
+use std::sync::Arc;
+
+/// Importing the "Mutex" structure
+/// to share data between threads.
+use std::sync::Mutex;
+
+/// Importing the standard
+/// "Result" enum.
+use std::fmt::Result;
+
+/// Importing the standard
+/// "Display" trait.
+use std::fmt::Display;
+
+/// Importing the standard
+/// "Formatter" trait.
+use std::fmt::Formatter;
+
+/// Importing the structure that
+/// holds what is known about
+/// a problem.
+use super::err::ErrInfo;
+
+/// Importing the function that
+/// returns the default directory
+/// for compiled projects.
+use super::config::default_dist_dir;
+
+/// Importing the function that
+/// returns the default field of
+/// an entry used for its slug.
+use super::generators::default_slug_field;
+
+/// Importing the "Sender" structure
+/// to send messages between threads.
+use std::sync::mpsc::Sender;
+
+/// An enum that describes the type
+/// the fields of a column of a CSV
+/// file are read as.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CsvColumnType {
+    Integer,
+    Float,
+    Boolean,
+    Text
+}
+
+/// An enum that describes the formats
+/// of files accepted by Mandy: JSON, YAML,
+/// TOML, and CSV. CSV is only accepted
+/// for data files.
 #[derive(Debug, Clone)]
 pub enum UserDataFileType {
     JSON,
-    YAML
+    YAML,
+    TOML,
+    CSV
 }
 
 /// A structure to supply context
-/// to Liquid templates.
+/// to Liquid templates. The context
+/// borrows everything it holds so that
+/// it can be built cheaply for every page.
+/// The page is either a content file or
+/// a page made by a page generator.
 #[derive(ObjectView,ValueView, Debug)]
-pub struct SiteContext{
-    pub site: MandyConfig,
-    pub page: MandyContent,
-    pub loop_content: Option<HashMap<String, Vec<MandyContent>>>,
-    pub data: Option<HashMap<String, Vec<HashMap<String,String>>>>,
-    pub baseurl: String
+pub struct SiteContext<'a>{
+    pub site: &'a MandyConfig,
+    pub page: &'a dyn ValueView,
+    pub loop_content: &'a Option<HashMap<String, Vec<MandyContent>>>,
+    pub data: &'a Option<HashMap<String, Value>>,
+    pub baseurl: &'a String
+}
 
+/// A structure to hold everything
+/// that is prepared only once per
+/// build for rendering pages.
+pub struct RenderInfo {
+    pub templates: HashMap<String, Template>,
+    pub sources: HashMap<String, Vec<(PathBuf, String)>>,
+    pub data: Option<HashMap<String, Value>>
 }
 
 /// A structure to hold all information
 /// gathered on a Mandy project and compile the
-/// project from this into a static site.
-#[derive(Debug)]
+/// project from this into a static site. If the
+/// project is compiled into a staging directory,
+/// "staging" holds the staging directory and the
+/// directory for the compiled project it stands
+/// in for.
+#[derive(Debug, Clone)]
 pub struct SiteInfo {
     pub config: ConfigFile,
     pub content_files: HashMap<PathBuf, MandyContent>,
+    pub generated_pages: HashMap<PathBuf, PathBuf>,
     pub data_files: Option<HashMap<String,UserDataFile>>,
     pub loop_content: Option<HashMap<String, Vec<MandyContent>>>,
     pub layouts: Vec<LayoutFile>,
     pub sass_dir: Option<PathBuf>,
-    pub partials: HashMap<String,String>
+    pub partials: HashMap<String,String>,
+    pub staging: Option<(PathBuf, PathBuf)>
 }
 
 /// A structure to hold information
@@ -79,7 +168,7 @@ pub struct UserDataFile {
     pub path: PathBuf,
     pub file_name: String,
     pub file_type: UserDataFileType,
-    pub contents: Vec<HashMap<String, String>>
+    pub contents: Value
 }
 
 /// A structure to hold information
@@ -89,26 +178,91 @@ pub struct UserDataFile {
 pub struct ConfigFile{
     pub path: PathBuf,
     pub file_type: UserDataFileType,
+    pub overlay: Option<PathBuf>,
+    pub environment: String,
+    pub unknown_keys: Vec<ErrInfo>,
     pub contents: MandyConfig
 }
 
 /// A structure to hold information
 /// on a Mandy project's configuration
-/// options.
+/// options. Only the "title" option
+/// must be set. The "dist_dir" option
+/// defaults to "dist" and all other
+/// options default to being empty.
 #[derive(Deserialize, ObjectView, ValueView, Debug, Clone)]
 pub struct MandyConfig {
+    #[serde(default)]
     pub tl_domain: String,
+    #[serde(default)]
     pub seo: bool,
+    #[serde(default)]
     pub title: String,
+    #[serde(default = "default_dist_dir")]
     pub dist_dir: String,
+    #[serde(default)]
     pub description: String,
+    #[serde(default)]
     pub prod_url: String,
+    #[serde(default)]
     pub dev_url: String,
+    #[serde(default)]
     pub copy_files: bool,
+    #[serde(default)]
     pub has_loop_content: bool,
+    #[serde(default)]
     pub copy_entities: Option<Vec<String>>,
+    #[serde(default)]
     pub loop_content_dirs: Option<Vec<String>>,
-    pub user_config: HashMap<String, String>
+    #[serde(default)]
+    pub user_config: HashMap<String, Value>,
+    #[serde(default)]
+    pub generators: Option<Vec<PageGenerator>>,
+    #[serde(default)]
+    pub markdown: MarkdownOptions
+}
+
+/// A structure to hold the options
+/// for rendering Markdown. The "gfm"
+/// option turns on all GitHub-flavored
+/// constructs and every construct can
+/// also be turned on or off on its own.
+/// Raw HTML is escaped and math syntax
+/// is not parsed unless turned on.
+#[derive(Deserialize, ObjectView, ValueView, Debug, Clone, Default)]
+pub struct MarkdownOptions {
+    #[serde(default)]
+    pub gfm: bool,
+    #[serde(default)]
+    pub tables: Option<bool>,
+    #[serde(default)]
+    pub strikethrough: Option<bool>,
+    #[serde(default)]
+    pub task_lists: Option<bool>,
+    #[serde(default)]
+    pub autolinks: Option<bool>,
+    #[serde(default)]
+    pub footnotes: Option<bool>,
+    #[serde(default)]
+    pub raw_html: bool,
+    #[serde(default)]
+    pub math: bool
+}
+
+/// A structure to hold the options
+/// of a page generator, which renders
+/// a page with the supplied layout for
+/// every entry of a data file. The URL
+/// of every page is made by replacing
+/// ":slug" in the supplied URL with
+/// the slug of the entry.
+#[derive(Deserialize, ObjectView, ValueView, Debug, Clone)]
+pub struct PageGenerator {
+    pub data: String,
+    pub layout: String,
+    pub url: String,
+    #[serde(default = "default_slug_field")]
+    pub slug_field: String
 }
 
 /// A structure to hold information
@@ -117,7 +271,7 @@ pub struct MandyConfig {
 #[derive(Deserialize, ObjectView, ValueView, Debug, Clone)]
 pub struct ContentStore {
     pub layout: String,
-    pub params: HashMap<String, String>,
+    pub params: HashMap<String, Value>,
     pub content: String
 }
 
@@ -128,7 +282,7 @@ pub struct ContentStore {
 #[derive(Deserialize, ObjectView, ValueView, Debug, Clone)]
 pub struct MandyContent{
     pub layout: String,
-    pub params: HashMap<String, String>,
+    pub params: HashMap<String, Value>,
     pub content: String,
     pub url: String,
     pub path: String
@@ -161,19 +315,18 @@ pub struct SiteMap{
     pub urls: Vec<SiteMapUrl>
 }
 
-/// Implementing functions
-/// for the "SiteMap"
-/// structure.
-impl SiteMap{
-   
-    /// Returns aN XML representation
-    /// of this structure.
-    pub fn to_string(&self) -> String{
+/// Implementing the "Display" trait
+/// for the "SiteMap" structure to
+/// get an XML representation
+/// of it.
+impl Display for SiteMap{
+    fn fmt(&self, f: &mut Formatter) -> Result {
         let mut url_string_vec: Vec<String> = Vec::new();
         for url in &self.urls{
             url_string_vec.push(url.to_string());
         }
-        format!(
+        write!(
+            f,
             "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n{}</urlset>", 
             url_string_vec.join("\n")
         )
@@ -188,17 +341,14 @@ pub struct SiteMapUrl {
     pub url: String
 }
 
-/// Implementing functions
-/// for the "SiteMapUrl"
-/// structure.
-impl SiteMapUrl{
-
-    /// Returns aN XML representation
-    /// of this structure.
-    pub fn to_string(&self) ->String {
-        format!("<url><loc>{}</loc></url>", &self.url)
+/// Implementing the "Display" trait
+/// for the "SiteMapUrl" structure to
+/// get an XML representation
+/// of it.
+impl Display for SiteMapUrl{
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        write!(f, "<url><loc>{}</loc></url>", &self.url)
     }
-
 }
 
 /// A structure
@@ -208,4 +358,61 @@ impl SiteMapUrl{
 pub struct ComplexPath{
     pub on_disk_html_url: String,
     pub web_link: String
+}
+
+/// A structure to
+/// store a response
+/// sent by Mandy's
+/// development server.
+pub struct ServerResponse {
+    pub status: String,
+    pub content_type: String,
+    pub location: Option<String>,
+    pub body: Vec<u8>
+}
+
+/// An enum that describes what
+/// changed in a Mandy project. The
+/// build cache decides which pages
+/// are rendered again, so this only
+/// tells browsers whether to swap the
+/// stylesheet or reload the page.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RebuildKind {
+    Sass,
+    Full
+}
+
+/// A structure to hold
+/// the channels of all browsers
+/// connected to Mandy's development
+/// server for live reloading.
+#[derive(Clone)]
+pub struct ReloadClients {
+    pub senders: Arc<Mutex<Vec<Sender<String>>>>
+}
+
+/// A structure to hold
+/// the build cache of a
+/// Mandy project between
+/// builds.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct BuildCache {
+    pub files: HashMap<String, String>,
+    pub pages: HashMap<String, PageRecord>,
+    pub sass: Option<String>
+}
+
+/// A structure to hold
+/// what a single page was
+/// built from in the last
+/// build.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PageRecord {
+    pub source: String,
+    pub layout: String,
+    pub partials: Vec<String>,
+    pub data_files: Vec<String>,
+    pub uses_loop_content: bool,
+    pub fingerprint: String
 }
\ No newline at end of file
@@ -32,6 +32,11 @@ use crate::ComplexPath;
 /// structure to handle errors.
 use super::err::MandyErr;
 
+/// Importing the structure that
+/// holds what is known about
+/// an error.
+use super::err::ErrInfo;
+
 /// Importing the "PathBuf"
 /// structure to work with paths.
 use super::units::PathInfo;
@@ -56,6 +61,34 @@ use std::collections::HashMap;
 /// structure to mutate it.
 use super::units::UserDataFile;
 
+/// Importing the function to find
+/// out the environment built for.
+use super::environment::get_environment;
+
+/// Importing the name of the
+/// environment for working on a
+/// site locally.
+use super::environment::DEVELOPMENT;
+
+/// Importing the standard library  
+/// for working with the filesystem.
+use std::fs;
+
+/// Importing the "Value"
+/// enum to hold the values
+/// of data files.
+use liquid::model::Value;
+
+/// Importing the "Object"
+/// type to nest data files
+/// from subdirectories.
+use liquid::model::Object;
+
+/// Importing the structure that holds all
+/// configuration options set for a Mandy
+/// project.
+use super::units::MandyConfig;
+
 /// Searches for files ending in the supplied file extension in a directory.
 /// If this operation succeeds, a vector containing instances of the "PathBuf" structure is
 /// returned. If this operation fails, an error is returned. A "Result" type is
@@ -66,29 +99,21 @@ pub fn find_files_with_ending(project_dir: &String, ext: &str) -> Result<Option<
     for entry in parent{
         let dir_entry: DirEntry = match entry{
             Ok(dir_entry) => dir_entry,
-            Err(e) => return Err::<Option<Vec<PathBuf>>, MandyErr>(MandyErr::new(&e.to_string()))
+            Err(e) => return Err::<Option<Vec<PathBuf>>, MandyErr>(MandyErr::Io(Box::new(ErrInfo::from_source(e))).with_path(&PathBuf::from(project_dir)))
         };
         if dir_entry.file_type().is_file(){
             let path = dir_entry.path();
-            match path.extension(){
-                Some(file_ext) => {
-                    let my_ext = file_ext.to_str();
-                    match my_ext {
-                        Some(cleaned) => {
-                            if cleaned == ext{
-                                let mut buf = PathBuf::new();
-                                buf.push(path);
-                                res.push(buf);
-                            }
-                            else {}
-                        },
-                        None => {}
-                    } 
+            if let Some(file_ext) = path.extension() {
+                let my_ext = file_ext.to_str();
+                if let Some(cleaned) = my_ext {
+                    if cleaned == ext{
+                        let mut buf = PathBuf::new();
+                        buf.push(path);
+                        res.push(buf);
+                    }
                 }
-                None => {}
-            };
+            }
         }
-        else {}
     }
     if res.is_empty(){ Ok(None) }
     else { Ok(Some(res)) }
@@ -133,18 +158,15 @@ pub fn extract_stem(subject: &String) -> Result<String, MandyErr>{
 /// Returns a "Result" type containing either an instance of the "PathInfo"
 /// structure or an error. This functions attempts to extract the file stem
 /// and the parent path of the given path. Returns an error if this operation fails.
-pub fn get_path_from_buf(subject: &PathBuf) -> Result<PathInfo, MandyErr>{
+pub fn get_path_from_buf(subject: &Path) -> Result<PathInfo, MandyErr>{
     let parent: PathBuf = match subject.parent(){
         Some(par) => par.to_path_buf(),
         None => {
-            let e: String = format!("Could not retrieve parent directory of path \"{}\".", subject.display().to_string());
+            let e: String = format!("Could not retrieve parent directory of path \"{}\".", subject.display());
             return Err::<PathInfo, MandyErr>(MandyErr::new(&e.to_string()))
         }
     };
-    let stem: String = match extract_stem(&subject.display().to_string()){
-        Ok(stem) => stem,
-        Err(e) => return Err::<PathInfo, MandyErr>(MandyErr::new(&e.to_string()))
-    };
+    let stem: String = extract_stem(&subject.display().to_string())?;
     Ok(PathInfo{ path: parent, file: stem})
 }
 
@@ -157,11 +179,10 @@ pub fn get_layout_by_name(layout: &String, layouts: &Vec<LayoutFile>) -> Result<
         if layout == &user_layout.name {
             requested.push(user_layout.to_owned());
         }
-        else {}
     }
     if requested.is_empty(){
         let e: String = format!("The requested layout \"{}\" could not be found.", layout);
-        return Err::<LayoutFile, MandyErr>(MandyErr::new(&e.to_string()));
+        Err::<LayoutFile, MandyErr>(MandyErr::Template(Box::new(ErrInfo::new(&e.to_string()))))
     }
     else {
         Ok(requested[0].clone())
@@ -171,9 +192,9 @@ pub fn get_layout_by_name(layout: &String, layouts: &Vec<LayoutFile>) -> Result<
 /// This function attempts to split an instance
 /// of the "PathBuf" structure into a vector of strings.
 /// A "Result" type is returned.
-pub fn split_path_buf(buf: &PathBuf) -> Result<Vec<String>, MandyErr>{
+pub fn split_path_buf(buf: &Path) -> Result<Vec<String>, MandyErr>{
     let mut result: Vec<String> = Vec::new();
-    let buf_components = buf.as_path().components();
+    let buf_components = buf.components();
     for component in buf_components{
         let component_string: String = match component.as_os_str().to_str(){
             Some(comp) => comp.to_string(),
@@ -196,11 +217,8 @@ pub fn string_vec_to_path_buf(subject: &Vec<String>) -> PathBuf{
 
 /// Attempts to split an instance of the "PathBuf" structure at the given item.
 /// A "Result" type is returned.
-pub fn split_buf_at_item(buf: &PathBuf, item: &String, including: &bool) -> Result<PathBuf, MandyErr>{
-    let buf_items: Vec<String> = match split_path_buf(buf){
-        Ok(buf_items) => buf_items,
-        Err(e) => return Err::<PathBuf, MandyErr>(MandyErr::new(&e.to_string()))
-    };
+pub fn split_buf_at_item(buf: &Path, item: &String, including: &bool) -> Result<PathBuf, MandyErr>{
+    let buf_items: Vec<String> = split_path_buf(buf)?;
     let mut cloned: Vec<String> = buf_items.clone();
     for (index,buf_item) in buf_items.iter().enumerate(){
         if buf_item == item && *including{
@@ -229,19 +247,16 @@ pub fn split_buf_at_item(buf: &PathBuf, item: &String, including: &bool) -> Resu
 /// "PathBuf" structure at the given item.
 /// A "Result" type is returned.
 pub fn insert_entity_into_buf_at_item(
-    buf: &PathBuf,
+    buf: &Path,
     entity: &String, 
     item: &String,
     direction: &Direction
 ) -> Result<PathBuf, MandyErr>{
-    let item_vec: Vec<String> = match split_path_buf(buf){
-        Ok(item_vec) => item_vec,
-        Err(e) => return Err::<PathBuf, MandyErr>(MandyErr::new(&e.to_string()))
-    };
+    let item_vec: Vec<String> = split_path_buf(buf)?;
     let mut cloned: Vec<String> = item_vec.clone();
     let item_idx: usize = match coutils::get_index(&item_vec, item){
         Ok(item_idx) => item_idx,
-        Err(e) => return Err::<PathBuf, MandyErr>(MandyErr::new(&e.to_string()))
+        Err(e) => return Err::<PathBuf, MandyErr>(MandyErr::Other(Box::new(ErrInfo::new(&e.to_string()))))
     };
     match direction {
         Direction::Before => {
@@ -257,42 +272,127 @@ pub fn insert_entity_into_buf_at_item(
     Ok(result)
 }
 
-/// Cleans and converts a series of the "UserDataFile" structure into a vector of string maps.
-/// A "Result" type is returned.
-pub fn clean_data(subject: &HashMap<String, UserDataFile>) -> HashMap<String, Vec<HashMap<String,String>>>{
-    let mut result: HashMap<String, Vec<HashMap<String, String>>> = HashMap::new();
-    for (_key,value) in subject{
-        let new_key: String = value.file_name.clone();
-        let new_value: Vec<HashMap<String,String>> = value.contents.clone();
-        result.insert(new_key, new_value);
+/// Cleans and converts a series of the "UserDataFile" structure into a map of
+/// the names of the data files to the values they hold. Data files in
+/// subdirectories are nested in a map for every subdirectory, so that
+/// "data/en/menu.yml" is found at "data.en.menu".
+pub fn clean_data(subject: &HashMap<String, UserDataFile>) -> HashMap<String, Value>{
+    let mut result: HashMap<String, Value> = HashMap::new();
+    for value in subject.values(){
+        let segments: Vec<&str> = value.file_name.split('/').collect();
+        let (new_key, nested) = match segments.split_first(){
+            Some(split) => split,
+            None => continue
+        };
+        let mut new_value: Value = value.contents.clone();
+        for segment in nested.iter().rev() {
+            let mut namespace: Object = Object::new();
+            namespace.insert(segment.to_string().into(), new_value);
+            new_value = Value::Object(namespace);
+        }
+        match result.get_mut(*new_key){
+            Some(existing) => merge_data_values(existing, new_value),
+            None => {
+                result.insert(new_key.to_string(), new_value);
+            }
+        };
     }
     result
 }
 
+/// Merges the supplied namespace of data files into another one.
+/// Maps are merged key by key and any other value is replaced.
+pub fn merge_data_values(base: &mut Value, other: Value){
+    match (base, other) {
+        (Value::Object(base_map), Value::Object(other_map)) => {
+            for (key, value) in other_map {
+                match base_map.get_mut(&key){
+                    Some(existing) => merge_data_values(existing, value),
+                    None => {
+                        base_map.insert(key, value);
+                    }
+                };
+            }
+        },
+        (base, other) => *base = other
+    };
+}
+
+/// Names the data file at the supplied path after its path inside the
+/// supplied data directory, without its file extension and with its
+/// directories separated by "/", for example "en/menu" for the file
+/// "data/en/menu.yml". Returns a "Result" type.
+pub fn get_data_name(data_dir: &PathBuf, path: &Path) -> Result<String, MandyErr>{
+    let relative: &Path = match path.strip_prefix(data_dir){
+        Ok(relative) => relative,
+        Err(_e) => {
+            let e: String = format!("The data file \"{}\" is not inside the directory \"{}\".", path.display(), data_dir.display());
+            return Err::<String, MandyErr>(MandyErr::Content(Box::new(ErrInfo::new(&e.to_string()))).with_path(path))
+        }
+    };
+    let mut segments: Vec<String> = Vec::new();
+    if let Some(parent) = relative.parent() {
+        for component in parent.components() {
+            segments.push(component.as_os_str().to_string_lossy().to_string());
+        }
+    }
+    match relative.file_stem(){
+        Some(stem) => segments.push(stem.to_string_lossy().to_string()),
+        None => {
+            let e: String = format!("Could not extract filename from path \"{}\"!", path.display());
+            return Err::<String, MandyErr>(MandyErr::Content(Box::new(ErrInfo::new(&e.to_string()))).with_path(path))
+        }
+    };
+    Ok(segments.join("/"))
+}
+
+/// Returns the name of the top-level namespace the data file with
+/// the supplied name is found in, for example "en" for "en/menu".
+pub fn get_data_namespace(name: &str) -> String {
+    match name.split_once('/'){
+        Some((namespace, _rest)) => namespace.to_string(),
+        None => name.to_string()
+    }
+}
+
 /// Attempts to return a string containing an instance of the "PathBuf"
 /// structure that has been reformatted to be an URL path.
-pub fn make_web_friendly(path: &PathBuf) -> Result<String, MandyErr> {
-    let strings: Vec<String> = match split_path_buf(path){
-        Ok(strings) => strings,
-        Err(e) => return Err::<String,MandyErr>(MandyErr::new(&e.to_string()))
-    };
+pub fn make_web_friendly(path: &Path) -> Result<String, MandyErr> {
+    let strings: Vec<String> = split_path_buf(path)?;
     let orig: String = strings.join("/");
     Ok(format!("/{}", orig))    
 }
 
+/// Attempts to move the supplied path of a file inside the project directory
+/// into the output directory, so that "site/posts/a.markdown" becomes
+/// "site/dist/posts/a.markdown". The project directory may be any path.
+/// A "Result" type is returned.
+pub fn get_dist_path(dir: &String, dist_dir: &String, path: &Path) -> Result<PathBuf, MandyErr>{
+    let relative: &Path = match path.strip_prefix(dir){
+        Ok(relative) => relative,
+        Err(_e) => {
+            let e: String = format!("The file \"{}\" is not inside the project directory \"{}\".", path.display(), dir);
+            return Err::<PathBuf, MandyErr>(MandyErr::Content(Box::new(ErrInfo::new(&e))).with_path(path))
+        }
+    };
+    let mut dist_buf: PathBuf = PathBuf::new();
+    dist_buf.push(dir);
+    dist_buf.push(dist_dir);
+    dist_buf.push(relative);
+    Ok(dist_buf)
+}
+
 /// Attempts to prepare an instance of the "PathBuf" structure linking to a content file
-/// for use with routing to HTML pages. A "Result" type is returned.
-pub fn produce_complex_path(path: &PathBuf, dist_dir: &String) -> Result<ComplexPath, MandyErr>{
+/// inside the supplied output directory for use with routing to HTML pages. A "Result" 
+/// type is returned.
+pub fn produce_complex_path(path: &Path, dist_buf: &PathBuf) -> Result<ComplexPath, MandyErr>{
     
     // Cleaning up the path of the HTML file on disk.
-    let stem: String = match extract_stem(&path.display().to_string()){
-        Ok(stem) => stem,
-        Err(e) => return Err::<ComplexPath,MandyErr>(MandyErr::new(&e.to_string()))
-    };
-    let mut orig_path_clone: PathBuf = path.clone();
+    let stem: String = extract_stem(&path.display().to_string())?;
+    let mut orig_path_clone: PathBuf = path.to_path_buf();
     orig_path_clone.set_extension("");
     let mut new_path_buf: PathBuf = orig_path_clone;
-    if stem == "index".to_string(){
+    if stem == "index"{
         new_path_buf.set_extension("html");
     }
     else {
@@ -300,13 +400,219 @@ pub fn produce_complex_path(path: &PathBuf, dist_dir: &String) -> Result<Complex
     }
 
     // Cleaning up the path of the weblink.
-    let split_buf: PathBuf = match split_buf_at_item(&new_path_buf.clone(), dist_dir, &true){
-        Ok(split_buf) => split_buf,
-        Err(e) => return Err::<ComplexPath,MandyErr>(MandyErr::new(&e.to_string()))
-    };
-    let web_link: String = match make_web_friendly(&split_buf){
-        Ok(web_link) => web_link,
-        Err(e) => return Err::<ComplexPath,MandyErr>(MandyErr::new(&e.to_string()))
+    let split_buf: PathBuf = match new_path_buf.strip_prefix(dist_buf){
+        Ok(split_buf) => split_buf.to_path_buf(),
+        Err(_e) => {
+            let e: String = format!("The page \"{}\" is not inside the output directory \"{}\".", new_path_buf.display(), dist_buf.display());
+            return Err::<ComplexPath,MandyErr>(MandyErr::Content(Box::new(ErrInfo::new(&e))).with_path(path))
+        }
     };
-    Ok(ComplexPath{ on_disk_html_url: new_path_buf.display().to_string(), web_link: web_link})
+    let web_link: String = make_web_friendly(&split_buf)?;
+    Ok(ComplexPath{ on_disk_html_url: new_path_buf.display().to_string(), web_link})
+}
+
+/// Attempts to retrieve the base URL for the environment built for. The
+/// "development" environment uses the "dev_url" option and every other
+/// environment uses the "prod_url" option, which an overlay file for the
+/// environment may replace. A "Result" type is returned.
+pub fn get_env_baseurl(config: &MandyConfig) -> Result<String, MandyErr>{
+    let environment: String = get_environment()?;
+    if environment == DEVELOPMENT {
+        Ok(config.dev_url.clone())
+    }
+    else {
+        Ok(config.prod_url.clone())
+    }
+}
+
+/// Attempts to copy the directory at the first path and all its contents
+/// to the second path. Existing files at the second path are removed before
+/// they are replaced.
+/// A "Result" type is returned.
+pub fn copy_directory(from: &PathBuf, to: &Path) -> Result<(), MandyErr>{
+    for entry in WalkDir::new(from){
+        let dir_entry: DirEntry = match entry{
+            Ok(dir_entry) => dir_entry,
+            Err(e) => return Err::<(), MandyErr>(MandyErr::Io(Box::new(ErrInfo::from_source(e))).with_path(from))
+        };
+        let relative: &Path = match dir_entry.path().strip_prefix(from){
+            Ok(relative) => relative,
+            Err(e) => return Err::<(), MandyErr>(MandyErr::Other(Box::new(ErrInfo::from_source(e))))
+        };
+        let mut target_buf: PathBuf = to.to_path_buf();
+        target_buf.push(relative);
+        if dir_entry.file_type().is_dir(){
+            let _create_op: () = match fs::create_dir_all(&target_buf){
+                Ok(_create_op) => _create_op,
+                Err(e) => return Err::<(), MandyErr>(MandyErr::Io(Box::new(ErrInfo::from_source(e))).with_path(&target_buf))
+            };
+        }
+        else {
+            if target_buf.is_file(){
+                let _del_op: () = match fs::remove_file(&target_buf){
+                    Ok(_del_op) => _del_op,
+                    Err(e) => return Err::<(), MandyErr>(MandyErr::Io(Box::new(ErrInfo::from_source(e))).with_path(&target_buf))
+                };
+            }
+            let _copy_op: u64 = match fs::copy(dir_entry.path(), &target_buf){
+                Ok(_copy_op) => _copy_op,
+                Err(e) => return Err::<(), MandyErr>(MandyErr::Io(Box::new(ErrInfo::from_source(e))).with_path(&target_buf))
+            };
+        }
+    }
+    Ok(())
+}
+
+/// Returns the paths that entities from the "copy_entities" option
+/// are copied to inside the directory containing the compiled Mandy
+/// project. Directories are copied by their name, files by their full
+/// relative path.
+pub fn get_copied_paths(dir: &String, config: &MandyConfig) -> Vec<PathBuf>{
+    let mut result: Vec<PathBuf> = Vec::new();
+    if let Some(entities) = &config.copy_entities {
+        for entity in entities {
+            let mut old_path_buf: PathBuf = PathBuf::new();
+            old_path_buf.push(dir);
+            old_path_buf.push(entity);
+            let mut new_path_buf: PathBuf = PathBuf::new();
+            new_path_buf.push(dir);
+            new_path_buf.push(&config.dist_dir);
+            if old_path_buf.is_dir(){
+                if let Some(dir_name) = old_path_buf.file_name() {
+                    new_path_buf.push(dir_name);
+                }
+            }
+            else {
+                new_path_buf.push(entity);
+            }
+            result.push(new_path_buf);
+        }
+    }
+    result
+}
+
+/// Tests for naming and
+/// nesting data files.
+#[cfg(test)]
+mod tests {
+
+    /// Importing the "PathBuf"
+    /// structure from Rust's standard
+    /// library.
+    use std::path::PathBuf;
+
+    /// Importing the "HashMap"
+    /// structure from Rust's standard
+    /// library.
+    use std::collections::HashMap;
+
+    /// Importing the "Value"
+    /// enum from the "liquid"
+    /// crate.
+    use liquid::model::Value;
+
+    /// Importing the structure
+    /// to hold information on a
+    /// data file.
+    use crate::modules::units::UserDataFile;
+
+    /// Importing the enum to
+    /// describe the type of a
+    /// data file.
+    use crate::modules::units::UserDataFileType;
+
+    /// Importing the function
+    /// to nest data files in
+    /// namespaces.
+    use super::clean_data;
+
+    /// Importing the function
+    /// to name data files.
+    use super::get_data_name;
+
+    /// Importing the function
+    /// to find the namespace of
+    /// a data file.
+    use super::get_data_namespace;
+
+    /// Importing the function to
+    /// move a content file into the
+    /// output directory.
+    use super::get_dist_path;
+
+    /// Importing the function to
+    /// route a content file to a
+    /// page.
+    use super::produce_complex_path;
+
+    /// Importing the structure
+    /// holding the routes of a
+    /// page.
+    use crate::ComplexPath;
+
+    /// Builds a data file with
+    /// the supplied name holding
+    /// the supplied value.
+    fn data_file(name: &str, contents: Value) -> UserDataFile {
+        UserDataFile {
+            path: PathBuf::from(format!("data/{}.json", name)),
+            file_name: name.to_string(),
+            file_type: UserDataFileType::JSON,
+            contents
+        }
+    }
+
+    /// Returns the value under the
+    /// supplied key of the supplied
+    /// map of values.
+    fn get_key<'a>(value: &'a Value, key: &str) -> &'a Value {
+        match value {
+            Value::Object(map) => map.get(key).unwrap(),
+            _ => panic!("\"{}\" was looked up in a value that is not a map.", key)
+        }
+    }
+
+    #[test]
+    fn names_data_files_after_their_paths() {
+        let data_dir: PathBuf = PathBuf::from("site/data");
+        assert_eq!(get_data_name(&data_dir, &PathBuf::from("site/data/en/menu.yml")).unwrap(), "en/menu");
+        assert_eq!(get_data_name(&data_dir, &PathBuf::from("site/data/site.json")).unwrap(), "site");
+        assert!(get_data_name(&data_dir, &PathBuf::from("other/site.json")).is_err());
+        assert_eq!(get_data_namespace(&String::from("en/menu")), "en");
+        assert_eq!(get_data_namespace(&String::from("site")), "site");
+    }
+
+    #[test]
+    fn nests_data_files_in_subdirectories() {
+        let mut files: HashMap<String, UserDataFile> = HashMap::new();
+        files.insert(String::from("en/menu"), data_file("en/menu", Value::scalar("menu".to_string())));
+        files.insert(String::from("en/footer"), data_file("en/footer", Value::scalar("footer".to_string())));
+        files.insert(String::from("de/blog/posts"), data_file("de/blog/posts", Value::scalar(3i64)));
+        files.insert(String::from("site"), data_file("site", Value::scalar(true)));
+        let data: HashMap<String, Value> = clean_data(&files);
+        assert_eq!(data.len(), 3);
+        assert_eq!(data.get("site"), Some(&Value::scalar(true)));
+        let english: &Value = data.get("en").unwrap();
+        assert_eq!(get_key(english, "menu"), &Value::scalar("menu".to_string()));
+        assert_eq!(get_key(english, "footer"), &Value::scalar("footer".to_string()));
+        let german: &Value = data.get("de").unwrap();
+        assert_eq!(get_key(get_key(german, "blog"), "posts"), &Value::scalar(3i64));
+    }
+
+    #[test]
+    fn routes_content_files_of_projects_at_any_path() {
+        let dist_dir: String = String::from("dist");
+        for dir in ["site", "/tmp/dist/my site", "."] {
+            let project: String = dir.to_string();
+            let md_file: PathBuf = PathBuf::from(dir).join("posts/hello.markdown");
+            let dist_path: PathBuf = get_dist_path(&project, &dist_dir, &md_file).unwrap();
+            assert_eq!(dist_path, PathBuf::from(dir).join("dist/posts/hello.markdown"));
+            let dist_buf: PathBuf = PathBuf::from(dir).join("dist");
+            let complex_path: ComplexPath = produce_complex_path(&dist_path, &dist_buf).unwrap();
+            assert_eq!(complex_path.web_link, "/posts/hello/index.html");
+            let index_path: PathBuf = get_dist_path(&project, &dist_dir, &PathBuf::from(dir).join("index.markdown")).unwrap();
+            assert_eq!(produce_complex_path(&index_path, &dist_buf).unwrap().web_link, "/index.html");
+        }
+        assert!(get_dist_path(&String::from("site"), &dist_dir, &PathBuf::from("other/index.markdown")).is_err());
+    }
 }
\ No newline at end of file
@@ -0,0 +1,174 @@
+/*
+Mandy by Alyx Shang.
+Licensed under the FSL v1.
+*/
+
+/// Importing the "PathBuf"
+/// structure from Rust's standard
+/// library.
+use std::path::PathBuf;
+
+/// Importing the structure that
+/// holds what is known about
+/// a problem.
+use super::err::ErrInfo;
+
+/// Importing the "SiteInfo"
+/// structure to read information
+/// gathered on a Mandy project.
+use super::units::SiteInfo;
+
+/// Importing the function to work
+/// out what a layout uses.
+use super::cache::find_layout_dependencies;
+
+/// Importing the function to return
+/// the paths and the code of all
+/// partial templates.
+use super::diagnostics::partial_sources;
+
+/// Importing the function to return
+/// the paths that entities from the
+/// "copy_entities" option are copied to.
+use super::utils::get_copied_paths;
+
+/// Importing the function to find
+/// the namespace a data file is
+/// found in.
+use super::utils::get_data_namespace;
+
+/// Finds problems in a Mandy project that do not stop it from being
+/// compiled: unknown options in the configuration file, layouts no
+/// content file uses, partial templates no layout includes, data files
+/// no template reads, and entities from the "copy_entities" option that
+/// overwrite generated pages. The problems
+/// are sorted by the path of the file they concern.
+pub fn find_warnings(dir: &String, data: &SiteInfo) -> Vec<ErrInfo> {
+    let mut warnings: Vec<ErrInfo> = data.config.unknown_keys.clone();
+    warnings.append(&mut find_unused_layouts(data));
+    warnings.append(&mut find_unused_partials(data));
+    warnings.append(&mut find_unread_data_files(data));
+    warnings.append(&mut find_overwritten_pages(dir, data));
+    warnings.sort_by(|a, b| (&a.path, &a.line).cmp(&(&b.path, &b.line)));
+    warnings
+}
+
+/// Finds all layouts that no content file uses.
+pub fn find_unused_layouts(data: &SiteInfo) -> Vec<ErrInfo> {
+    let mut warnings: Vec<ErrInfo> = Vec::new();
+    for layout in &data.layouts {
+        let is_used: bool = data.content_files
+            .values()
+            .any(|content| content.layout == layout.name);
+        if !is_used {
+            let message: String = format!("The layout \"{}\" is not used by any content file.", &layout.name);
+            let mut warning: ErrInfo = ErrInfo::new(&message);
+            warning.path = Some(layout.path.clone());
+            warnings.push(warning);
+        }
+    }
+    warnings
+}
+
+/// Finds all partial templates that no layout includes or
+/// renders, directly or through another partial template.
+/// Nothing is reported if a template includes a partial
+/// template by a name that is not a string literal.
+pub fn find_unused_partials(data: &SiteInfo) -> Vec<ErrInfo> {
+    let mut used: Vec<String> = Vec::new();
+    for layout in &data.layouts {
+        let (partials, _data_names, _uses_loop_content) = find_layout_dependencies(&layout.contents, &data.partials);
+        used.extend(partials);
+    }
+    let mut warnings: Vec<ErrInfo> = Vec::new();
+    for (partial_path, _source) in partial_sources(data) {
+        let name: String = match partial_path.file_stem(){
+            Some(stem) => stem.to_string_lossy().to_string(),
+            None => continue
+        };
+        if !used.contains(&name){
+            let message: String = format!("The partial template \"{}\" is not included by any layout.", &name);
+            let mut warning: ErrInfo = ErrInfo::new(&message);
+            warning.path = Some(partial_path);
+            warnings.push(warning);
+        }
+    }
+    warnings
+}
+
+/// Finds all data files that no layout, partial template, or page
+/// generator reads.
+/// Nothing is reported if a template uses the "data" namespace in
+/// a way that may read any data file.
+pub fn find_unread_data_files(data: &SiteInfo) -> Vec<ErrInfo> {
+    let mut warnings: Vec<ErrInfo> = Vec::new();
+    let data_files = match &data.data_files {
+        Some(data_files) => data_files,
+        None => return warnings
+    };
+    let mut read: Vec<String> = Vec::new();
+    if let Some(generators) = &data.config.contents.generators {
+        read.extend(generators.iter().map(|generator| generator.data.clone()));
+    }
+    for layout in &data.layouts {
+        let (_partials, data_names, _uses_loop_content) = find_layout_dependencies(&layout.contents, &data.partials);
+        match data_names {
+            Some(names) => read.extend(names),
+            None => return warnings
+        };
+    }
+    for (name, data_file) in data_files {
+        if !read.contains(&get_data_namespace(name)) && !read.contains(name){
+            let message: String = format!("The data file \"{}\" is not read by any template.", name);
+            let mut warning: ErrInfo = ErrInfo::new(&message);
+            warning.path = Some(data_file.path.clone());
+            warnings.push(warning);
+        }
+    }
+    warnings
+}
+
+/// Finds all entities from the "copy_entities" option that are copied
+/// over a page generated from a content file. Entities are copied after
+/// pages are rendered, so the copied file replaces the generated page.
+pub fn find_overwritten_pages(dir: &String, data: &SiteInfo) -> Vec<ErrInfo> {
+    let mut warnings: Vec<ErrInfo> = Vec::new();
+    if !data.config.contents.copy_files {
+        return warnings;
+    }
+    let entities: &Vec<String> = match &data.config.contents.copy_entities {
+        Some(entities) => entities,
+        None => return warnings
+    };
+    let copied_paths: Vec<PathBuf> = get_copied_paths(dir, &data.config.contents);
+    for (entity, copied_path) in entities.iter().zip(copied_paths.iter()) {
+        let mut entity_buf: PathBuf = PathBuf::new();
+        entity_buf.push(dir);
+        entity_buf.push(entity);
+        let mut sources: Vec<&PathBuf> = data.content_files.keys().collect();
+        sources.sort();
+        for source in sources {
+            let page_buf: PathBuf = PathBuf::from(&data.content_files[source].path);
+            let overwrites: bool = if entity_buf.is_dir() {
+                match page_buf.strip_prefix(copied_path){
+                    Ok(relative) => entity_buf.join(relative).is_file(),
+                    Err(_e) => false
+                }
+            }
+            else {
+                &page_buf == copied_path
+            };
+            if overwrites {
+                let message: String = format!(
+                    "The entity \"{}\" from the \"copy_entities\" option overwrites the page generated from \"{}\".",
+                    entity,
+                    source.display()
+                );
+                let mut warning: ErrInfo = ErrInfo::new(&message);
+                warning.path = Some(data.config.path.clone());
+                warnings.push(warning);
+            }
+        }
+    }
+    warnings
+}
\ No newline at end of file
@@ -0,0 +1,357 @@
+/*
+Mandy by Alyx Shang.
+Licensed under the FSL v1.
+*/
+
+/// Importing the standard
+/// library for working with
+/// the filesystem.
+use std::fs;
+
+/// Importing the "Duration"
+/// structure to wait for
+/// further changes.
+use std::time::Duration;
+
+/// Importing the "PathBuf"
+/// structure from Rust's standard
+/// library.
+use std::path::PathBuf;
+
+/// Importing the "Event"
+/// structure to receive
+/// changes to files.
+use notify::Event;
+
+/// Importing the "channel"
+/// function to receive
+/// changes to files.
+use std::sync::mpsc::channel;
+
+/// Importing the "Receiver"
+/// structure to receive
+/// changes to files.
+use std::sync::mpsc::Receiver;
+
+/// Importing the "Watcher"
+/// trait to watch a directory.
+use notify::Watcher;
+
+/// Importing the "EventKind"
+/// enum to tell what kind of
+/// change was made to a file.
+use notify::EventKind;
+
+/// Importing the "RecursiveMode"
+/// enum to watch all sub-directories.
+use notify::RecursiveMode;
+
+/// Importing Mandy's error-handling
+/// structure to handle errors.
+use super::err::MandyErr;
+
+/// Importing the structure that
+/// holds what is known about
+/// an error.
+use super::err::ErrInfo;
+
+/// Importing the watcher
+/// best suited for the
+/// current platform.
+use notify::RecommendedWatcher;
+
+/// Importing the structure that holds all
+/// information on a configuration file in a
+/// a Mandy project.
+use super::units::ConfigFile;
+
+/// Importing the enum that describes
+/// how a Mandy project should be
+/// rebuilt.
+use super::units::RebuildKind;
+
+/// Importing the function
+/// to read a Mandy project's
+/// configuration settings.
+use super::gather::read_config;
+
+/// Importing the function to retrieve
+/// the base URL for the current environment.
+use super::utils::get_env_baseurl;
+
+/// Importing the "SiteInfo"
+/// structure to store information
+/// about the Mandy project.
+use super::units::SiteInfo;
+
+/// Importing the function that
+/// gathers information about the Mandy project
+/// in the supplied directory.
+use super::gather::gather_project_data;
+
+/// Importing the function to compile a
+/// Mandy project from gathered information.
+use super::compile::compile_site_info;
+
+/// Importing the function to get the name
+/// of the staging directory.
+use super::compile::get_staging_dir;
+
+/// Importing the function to get the name
+/// of the directory holding the last build
+/// during a swap.
+use super::compile::get_previous_dir;
+
+/// Importing the function to compare
+/// paths from options without their
+/// "." components.
+use super::config::normalize_project_path;
+
+/// Importing the name of the
+/// file the build cache is
+/// stored in.
+use super::cache::CACHE_FILE;
+
+/// Importing the function
+/// to report an error.
+use super::logger::log_error;
+
+/// Importing the function
+/// to report the outcome
+/// of a rebuild.
+use super::logger::log_info;
+
+/// The time to wait for further changes
+/// after a change has been detected.
+pub const DEBOUNCE_MILLIS: u64 = 200;
+
+/// Decides whether a Mandy project should be rebuilt given the paths that
+/// changed. Changes inside the directory for the compiled project, its staging
+/// directory, and to the build cache are ignored. If only files in the "sass"
+/// directory changed, the rebuild only changes the stylesheet. Which pages are
+/// rendered again is left to the build cache. Returns "None" if no rebuild
+/// is needed.
+pub fn classify_changes(
+    project_buf: &PathBuf,
+    dist_dir: &String,
+    changed: &Vec<PathBuf>
+) -> Option<RebuildKind> {
+    let mut needs_full: bool = false;
+    let mut relevant: bool = false;
+    for path in changed {
+        let relative: PathBuf = match path.strip_prefix(project_buf){
+            Ok(relative) => relative.to_path_buf(),
+            Err(_e) => continue
+        };
+        let first: String = match relative.components().next(){
+            Some(first) => first.as_os_str().to_string_lossy().to_string(),
+            None => continue
+        };
+        let is_output: bool = relative.starts_with(normalize_project_path(dist_dir)) ||
+            relative.starts_with(get_staging_dir(dist_dir)) ||
+            relative.starts_with(get_previous_dir(dist_dir));
+        if is_output || first == CACHE_FILE {
+            continue;
+        }
+        relevant = true;
+        if first != "sass" {
+            needs_full = true;
+        }
+    }
+    if !relevant {
+        None
+    }
+    else if needs_full {
+        Some(RebuildKind::Full)
+    }
+    else {
+        Some(RebuildKind::Sass)
+    }
+}
+
+/// Rebuilds a Mandy project after a change. The project is gathered once
+/// for the rebuild. Every rebuild goes through the staging directory and the
+/// build cache, so only the pages or the stylesheet affected by the change
+/// are compiled again and a failed rebuild leaves the last good build in
+/// place. Returns a "Result" type with an empty closure.
+pub fn rebuild_project(
+    dir: &String,
+    baseurl: &String
+) -> Result<(), MandyErr> {
+    let data: SiteInfo = gather_project_data(dir)?;
+    compile_site_info(dir, &data, baseurl)
+}
+
+/// Waits for the next batch of changes to files from the supplied
+/// receiver. Changes arriving shortly after each other are collected
+/// into one batch. Returns the changed paths or "None" if the watcher
+/// has stopped.
+pub fn next_changes(receiver: &Receiver<notify::Result<Event>>) -> Option<Vec<PathBuf>> {
+    let mut changed: Vec<PathBuf> = Vec::new();
+    let mut waiting: bool = true;
+    while waiting || changed.is_empty() {
+        let received: notify::Result<Event> = if changed.is_empty(){
+            match receiver.recv(){
+                Ok(received) => received,
+                Err(_e) => return None
+            }
+        }
+        else {
+            match receiver.recv_timeout(Duration::from_millis(DEBOUNCE_MILLIS)){
+                Ok(received) => received,
+                Err(_e) => {
+                    waiting = false;
+                    continue;
+                }
+            }
+        };
+        let event: Event = match received {
+            Ok(event) => event,
+            Err(e) => {
+                log_error(&MandyErr::Io(Box::new(ErrInfo::from_source(e))));
+                continue;
+            }
+        };
+        if let EventKind::Access(_) = event.kind {
+            continue;
+        }
+        for path in event.paths {
+            if !changed.contains(&path){
+                changed.push(path);
+            }
+        }
+    }
+    Some(changed)
+}
+
+/// Compiles the Mandy project in the supplied directory and then watches
+/// the project directory for changes. The project is recompiled whenever
+/// a change is detected. Changes inside the directory containing the
+/// compiled project are ignored. This function only returns if an
+/// error occurs.
+pub fn watch_project(dir: &String) -> Result<(), MandyErr> {
+    let config: ConfigFile = read_config(dir)?;
+    let baseurl: String = get_env_baseurl(&config.contents)?;
+    let _build_op: () = rebuild_project(dir, &baseurl)?;
+    log_info(&format!("The Mandy project at \"{}\" has been compiled.", dir));
+    watch_for_changes(dir, &baseurl, &|_kind: &RebuildKind| {})
+}
+
+/// Watches the project directory for changes and rebuilds the Mandy project
+/// with the supplied base URL whenever a change is detected. The supplied
+/// function is called after every successful rebuild. This function only 
+/// returns if an error occurs.
+pub fn watch_for_changes(
+    dir: &String,
+    baseurl: &String,
+    on_rebuild: &dyn Fn(&RebuildKind)
+) -> Result<(), MandyErr> {
+    let project_buf: PathBuf = match fs::canonicalize(dir){
+        Ok(project_buf) => project_buf,
+        Err(e) => return Err::<(), MandyErr>(MandyErr::Io(Box::new(ErrInfo::from_source(e))).with_path(&PathBuf::from(dir)))
+    };
+    let (sender, receiver) = channel::<notify::Result<Event>>();
+    let mut watcher: RecommendedWatcher = match notify::recommended_watcher(sender){
+        Ok(watcher) => watcher,
+        Err(e) => return Err::<(), MandyErr>(MandyErr::Io(Box::new(ErrInfo::from_source(e))))
+    };
+    let _watch_op: () = match watcher.watch(&project_buf, RecursiveMode::Recursive){
+        Ok(_watch_op) => _watch_op,
+        Err(e) => return Err::<(), MandyErr>(MandyErr::Io(Box::new(ErrInfo::from_source(e))).with_path(&project_buf))
+    };
+    log_info(&format!("Watching the Mandy project at \"{}\" for changes.", dir));
+    while let Some(changed) = next_changes(&receiver) {
+        let dist_dir: String = match read_config(dir){
+            Ok(config) => config.contents.dist_dir,
+            Err(e) => {
+                log_error(&e);
+                continue;
+            }
+        };
+        let kind: RebuildKind = match classify_changes(&project_buf, &dist_dir, &changed){
+            Some(kind) => kind,
+            None => continue
+        };
+        match rebuild_project(dir, baseurl){
+            Ok(_rebuild_op) => {
+                log_info(&format!("The Mandy project at \"{}\" has been recompiled.", dir));
+                on_rebuild(&kind);
+            },
+            Err(e) => log_error(&e)
+        };
+    }
+    Ok(())
+}
+
+/// Tests for deciding how a
+/// project is rebuilt.
+#[cfg(test)]
+mod tests {
+
+    /// Importing the "PathBuf"
+    /// structure from Rust's standard
+    /// library.
+    use std::path::PathBuf;
+
+    /// Importing the enum that describes
+    /// how a Mandy project should be
+    /// rebuilt.
+    use crate::modules::units::RebuildKind;
+
+    /// Importing the function
+    /// under test.
+    use super::classify_changes;
+
+    /// Classifies changes to the supplied paths
+    /// inside the project "/site" with the
+    /// directory "dist" for the compiled project.
+    fn classify(paths: &[&str]) -> Option<RebuildKind> {
+        let changed: Vec<PathBuf> = paths.iter().map(|path| PathBuf::from("/site").join(path)).collect();
+        classify_changes(&PathBuf::from("/site"), &String::from("dist"), &changed)
+    }
+
+    #[test]
+    fn ignores_output_and_the_build_cache() {
+        assert_eq!(classify(&["dist/index.html"]), None);
+        assert_eq!(classify(&[".dist-staging/index.html"]), None);
+        assert_eq!(classify(&[".dist-previous/index.html"]), None);
+        assert_eq!(classify(&[".mandy-cache"]), None);
+        assert_eq!(classify(&[]), None);
+    }
+
+    #[test]
+    fn ignores_output_under_dotted_and_nested_names() {
+        let changed: Vec<PathBuf> = vec![
+            PathBuf::from("/site/dist/index.html"),
+            PathBuf::from("/site/.dist-staging/index.html"),
+            PathBuf::from("/site/.dist-previous/index.html")
+        ];
+        assert_eq!(classify_changes(&PathBuf::from("/site"), &String::from("./dist"), &changed), None);
+        assert_eq!(classify_changes(&PathBuf::from("/site"), &String::from("dist/"), &changed), None);
+        let nested: Vec<PathBuf> = vec![
+            PathBuf::from("/site/out/site/index.html"),
+            PathBuf::from("/site/out/.site-staging/index.html")
+        ];
+        assert_eq!(classify_changes(&PathBuf::from("/site"), &String::from("./out/site"), &nested), None);
+    }
+
+    #[test]
+    fn ignores_paths_outside_the_project() {
+        let changed: Vec<PathBuf> = vec![PathBuf::from("/elsewhere/index.markdown")];
+        assert_eq!(classify_changes(&PathBuf::from("/site"), &String::from("dist"), &changed), None);
+    }
+
+    #[test]
+    fn swaps_the_stylesheet_for_sass_only_changes() {
+        assert_eq!(classify(&["sass/index.scss"]), Some(RebuildKind::Sass));
+        assert_eq!(classify(&["sass/index.scss", "dist/css/index.css"]), Some(RebuildKind::Sass));
+    }
+
+    #[test]
+    fn reloads_for_any_other_change() {
+        assert_eq!(classify(&["index.markdown"]), Some(RebuildKind::Full));
+        assert_eq!(classify(&["layouts/default.liquid"]), Some(RebuildKind::Full));
+        assert_eq!(classify(&["sass/index.scss", "partials/head.liquid"]), Some(RebuildKind::Full));
+        assert_eq!(classify(&["config.yml", "dist/index.html"]), Some(RebuildKind::Full));
+    }
+}
\ No newline at end of file